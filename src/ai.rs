@@ -1,203 +1,1172 @@
+//! The computer opponent, placing its fleet and picking where to shoot
+
 extern crate rand;
 
+use crate::board::Board;
 use crate::config;
-use crate::game;
-use crate::movement;
-use crate::ships;
+use crate::density::Density;
+use crate::engine::Rules;
+use crate::game::HitType;
+use crate::movement::{self, Adjacency};
+use crate::placement::{self, Placement};
+use crate::profile::Profile;
+use crate::ships::{self, PlacedShip};
+use crate::solver;
 use crate::Cell;
+use crate::GameRng;
 use crate::Rotation;
 
-use rand::Rng;
-use ships::ShipTracker;
-
-pub fn set_ships(mut board: config::Board) -> config::Board {
-	let mut ships =
-		ShipTracker::new(config::SHIP_ONE_BLOCK_AMOUNT, config::SHIP_TWO_BLOCK_AMOUNT, config::SHIP_THREE_BLOCK_AMOUNT);
-	let (one_block, two_block, three_block) = ships.get_ships();
-	let mut remaining_ships = one_block + two_block + three_block;
-	let mut this_ship = ships.get_next_unset_ship().unwrap();
-	let mut ship_size = config::get_entitie_size(&this_ship);
-
-	while remaining_ships > 0 {
-		let pos_x: usize = rand::thread_rng().gen_range(0..config::SIZE_X);
-		let pos_y: usize = rand::thread_rng().gen_range(0..config::SIZE_Y);
-		let rotation = match rand::thread_rng().gen_range(0..=1) {
-			0 => Rotation::Horizontal,
-			_ => Rotation::Vertical,
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use ships::{ShipClass, ShipTracker};
+use std::cell::RefCell;
+
+/// Why a fleet can't be placed on a board
+#[derive(Clone, Debug, PartialEq)]
+pub enum FleetError {
+	/// A ship of this class is longer than both sides of the board
+	ShipTooLong(String),
+	/// The fleet takes up more cells than the board has left
+	NotEnoughSpace { needed: usize, free: usize },
+	/// Every cell could fit but there is no way to arrange the ships without breaking a rule
+	NoLayout,
+	/// No free position is left for the next ship of this class next to the ships that are already placed
+	NoRoom(String),
+	/// The strategy gave up placing its fleet for this reason, like a bot that crashed or broke a rule
+	Forfeit(String),
+}
+
+/// Place the whole fleet at random positions on the board next to the ships that are already on it
+///
+/// Every legal layout is about equally likely. When the fleet can't be placed the board is left untouched and the
+/// reason is returned instead.
+pub fn set_ships(
+	board: Board,
+	fleet: &[ShipClass],
+	adjacency: Adjacency,
+	rng: &mut GameRng,
+) -> Result<Board, FleetError> {
+	if let Some(kind) = fleet.iter().find(|kind| kind.amount > 0 && kind.size > board.size_x().max(board.size_y())) {
+		return Err(FleetError::ShipTooLong(kind.name.clone()));
+	}
+
+	let needed = ships::get_fleet_size(fleet);
+	let free = get_free_cells(&board);
+	if needed > free {
+		return Err(FleetError::NotEnoughSpace { needed, free });
+	}
+
+	// biggest ships first so a sample that is going to conflict gives up early
+	let mut ships: Vec<ShipClass> = Vec::new();
+	let mut tracker = ShipTracker::new(fleet);
+	while let Some(kind) = tracker.get_next_unset_ship() {
+		tracker.set_ship(&kind);
+		ships.push(kind);
+	}
+	ships.sort_by_key(|kind| std::cmp::Reverse(kind.size));
+
+	// drawing every ship independently and starting over on any conflict gives each layout the same chance
+	for _ in 0..config::PLACEMENT_SAMPLES {
+		if let Some(board) = sample_layout(&board, &ships, adjacency, rng) {
+			return Ok(board);
+		}
+	}
+
+	// crowded boards rarely survive sampling so we search all layouts in random order instead
+	let mut remaining: Vec<(ShipClass, usize)> = fleet.iter().map(|kind| (kind.clone(), kind.amount)).collect();
+	search_layout(&board, &mut remaining, 0, adjacency, rng).ok_or(FleetError::NoLayout)
+}
+
+fn get_free_cells(board: &Board) -> usize {
+	board.rows().flatten().filter(|cell| **cell == Cell::Empty || **cell == Cell::Placeholder).count()
+}
+
+// the free cells from the cursor onwards a ship of this size could still be placed on, the smallest ship reaches
+// every cell any other ship could use
+fn get_usable_cells(board: &Board, ship_size: usize, cursor: usize, adjacency: Adjacency) -> Vec<bool> {
+	let mut usable = vec![false; board.size_x() * board.size_y()];
+
+	for (pos_x, pos_y, rotation) in get_candidates(board, ship_size) {
+		if pos_y * board.size_x() + pos_x >= cursor
+			&& movement::check_placement(board, pos_x as isize, pos_y as isize, ship_size, &rotation, adjacency).is_ok()
+		{
+			for (x, y) in movement::get_coordinates(pos_x, pos_y, ship_size, &rotation) {
+				usable[y * board.size_x() + x] = true;
+			}
+		}
+	}
+
+	usable
+}
+
+// an upper bound for how many more ships fit, when ships may not touch each 2x2 block can only hold cells of one ship
+// or of two diagonal ones
+fn get_ship_capacity(board: &Board, usable: &[bool], adjacency: Adjacency) -> usize {
+	let per_block = match adjacency {
+		Adjacency::Any => return usable.iter().filter(|cell| **cell).count(),
+		Adjacency::NoEdge => 2,
+		Adjacency::NoEdgeOrCorner => 1,
+	};
+
+	let mut blocks = 0;
+	for block_y in (0..board.size_y()).step_by(2) {
+		for block_x in (0..board.size_x()).step_by(2) {
+			let is_usable = (block_y..(block_y + 2).min(board.size_y()))
+				.any(|y| (block_x..(block_x + 2).min(board.size_x())).any(|x| usable[y * board.size_x() + x]));
+			if is_usable {
+				blocks += 1;
+			}
+		}
+	}
+
+	blocks * per_block
+}
+
+// every position on the board a ship of this size would fit if the board was empty
+pub(crate) fn get_candidates(board: &Board, ship_size: usize) -> Vec<(usize, usize, Rotation)> {
+	let mut candidates = Vec::new();
+
+	for pos_y in 0..board.size_y() {
+		for pos_x in 0..board.size_x() {
+			if pos_x + ship_size <= board.size_x() {
+				candidates.push((pos_x, pos_y, Rotation::Horizontal));
+			}
+			// a ship of one cell looks the same in both rotations
+			if ship_size > 1 && pos_y + ship_size <= board.size_y() {
+				candidates.push((pos_x, pos_y, Rotation::Vertical));
+			}
+		}
+	}
+
+	candidates
+}
+
+fn sample_layout<R: Rng>(board: &Board, ships: &[ShipClass], adjacency: Adjacency, rng: &mut R) -> Option<Board> {
+	let mut board = board.clone();
+
+	for kind in ships {
+		let (pos_x, pos_y, rotation) = *get_candidates(&board, kind.size).choose(rng)?;
+		movement::check_placement(&board, pos_x as isize, pos_y as isize, kind.size, &rotation, adjacency).ok()?;
+		board = movement::place_ship(board, pos_x, pos_y, kind, &rotation);
+	}
+
+	Some(board)
+}
+
+// walk the board cell by cell, every cell either becomes the first cell of one of the remaining ships or stays
+// water, cells before the cursor are settled so ships of the same class are never tried in swapped order
+fn search_layout<R: Rng>(
+	board: &Board,
+	remaining: &mut [(ShipClass, usize)],
+	cursor: usize,
+	adjacency: Adjacency,
+	rng: &mut R,
+) -> Option<Board> {
+	let needed: usize = remaining.iter().map(|(kind, amount)| kind.size * amount).sum();
+	if needed == 0 {
+		return Some(board.clone());
+	}
+
+	let smallest = remaining.iter().filter(|(_, amount)| *amount > 0).map(|(kind, _)| kind.size).min()?;
+	let usable = get_usable_cells(board, smallest, cursor, adjacency);
+	let ships: usize = remaining.iter().map(|(_, amount)| amount).sum();
+	if needed > usable.iter().filter(|cell| **cell).count() || ships > get_ship_capacity(board, &usable, adjacency) {
+		return None;
+	}
+
+	let cells = board.size_x() * board.size_y();
+	let cursor = (cursor..cells).find(|index| {
+		let cell = board[index / board.size_x()][index % board.size_x()];
+		cell == Cell::Empty || cell == Cell::Placeholder
+	})?;
+	let (pos_x, pos_y) = (cursor % board.size_x(), cursor / board.size_x());
+
+	// None leaves the cell empty
+	let mut options: Vec<Option<(usize, Rotation)>> = vec![None];
+	for (index, (kind, amount)) in remaining.iter().enumerate() {
+		for rotation in [Rotation::Horizontal, Rotation::Vertical] {
+			if *amount > 0
+				&& !(kind.size == 1 && rotation == Rotation::Vertical)
+				&& movement::check_placement(board, pos_x as isize, pos_y as isize, kind.size, &rotation, adjacency).is_ok()
+			{
+				options.push(Some((index, rotation)));
+			}
+		}
+	}
+	options.shuffle(rng);
+
+	for option in options {
+		let result = match option {
+			Some((index, rotation)) => {
+				let next = movement::place_ship(board.clone(), pos_x, pos_y, &remaining[index].0, &rotation);
+				remaining[index].1 -= 1;
+				let result = search_layout(&next, remaining, cursor + 1, adjacency, rng);
+				remaining[index].1 += 1;
+				result
+			}
+			None => search_layout(board, remaining, cursor + 1, adjacency, rng),
 		};
 
-		if movement::is_free_space(&board, pos_x as isize, pos_y as isize, ship_size, &rotation) {
-			board = movement::place_entity(board, pos_x, pos_y, ship_size, &rotation, Cell::Ship);
-			ships.set_ship(&this_ship);
-			match ships.get_next_unset_ship() {
-				Some(kind) => {
-					this_ship = kind;
-					ship_size = config::get_entitie_size(&this_ship);
-				}
-				None => {
-					remaining_ships = 0;
-				}
-			};
+		if result.is_some() {
+			return result;
 		}
 	}
 
-	board
+	None
 }
 
 #[test]
 fn set_ships_works() {
+	let mut rng = GameRng::seed_from_u64(0);
 	let mut ships = 0;
-	let board = set_ships([[Cell::Empty; config::SIZE_X]; config::SIZE_Y]);
-	for row in board {
+	let fleet = config::get_fleet();
+	let board = set_ships(Board::new(config::MIN_SIZE_X, config::MIN_SIZE_Y), &fleet, Adjacency::Any, &mut rng).unwrap();
+	for row in &board {
 		for cell in row {
-			match cell {
-				Cell::ShipOne(_) | Cell::ShipTwo(_) | Cell::ShipThree(_) => {
-					ships += 1;
+			if let Cell::Ship(_) = cell {
+				ships += 1;
+			}
+		}
+	}
+
+	assert_eq!(ships, ships::get_fleet_size(&fleet));
+	assert_eq!(board.fleet().get_ships().len(), 4);
+}
+
+#[test]
+fn set_ships_fails_when_the_fleet_does_not_fit() {
+	let mut rng = GameRng::seed_from_u64(0);
+	let board = Board::new(config::MIN_SIZE_X, config::MIN_SIZE_Y);
+	assert_eq!(
+		set_ships(board.clone(), &[ShipClass::new("Carrier", 10, 1)], Adjacency::Any, &mut rng),
+		Err(FleetError::ShipTooLong(String::from("Carrier")))
+	);
+	assert_eq!(
+		set_ships(board.clone(), &[ShipClass::new("Destroyer", 2, 19)], Adjacency::Any, &mut rng),
+		Err(FleetError::NotEnoughSpace { needed: 38, free: 36 })
+	);
+	// only one carrier fits into each row and none fit upright
+	assert_eq!(
+		set_ships(board.clone(), &[ShipClass::new("Carrier", 5, 5)], Adjacency::Any, &mut rng),
+		Err(FleetError::NoLayout)
+	);
+
+	let board = set_ships(board, &[ShipClass::new("Carrier", 5, 4)], Adjacency::Any, &mut rng).unwrap();
+	assert_eq!(board.fleet().get_ships().len(), 4);
+}
+
+#[test]
+fn set_ships_fills_up_a_crowded_board() {
+	let mut rng = GameRng::seed_from_u64(0);
+	let board = set_ships(Board::new(9, 4), &[ShipClass::new("Cruiser", 3, 12)], Adjacency::Any, &mut rng).unwrap();
+	assert_eq!(get_free_cells(&board), 0);
+
+	let board = set_ships(board, &[], Adjacency::Any, &mut rng).unwrap();
+	assert_eq!(board.fleet().get_ships().len(), 12);
+	assert_eq!(
+		set_ships(board, &[ShipClass::new("Patrol boat", 1, 1)], Adjacency::Any, &mut rng),
+		Err(FleetError::NotEnoughSpace { needed: 1, free: 0 })
+	);
+}
+
+#[test]
+fn set_ships_keeps_ships_apart() {
+	let mut rng = GameRng::seed_from_u64(0);
+	for _ in 0..20 {
+		let board = set_ships(Board::default(), &config::get_classic_fleet(), Adjacency::NoEdgeOrCorner, &mut rng).unwrap();
+		for ship in board.fleet().get_ships() {
+			for (pos_x, pos_y) in &ship.cells {
+				for y in pos_y.saturating_sub(1)..=(pos_y + 1).min(board.size_y() - 1) {
+					for x in pos_x.saturating_sub(1)..=(pos_x + 1).min(board.size_x() - 1) {
+						if let Cell::Ship(id) = board[y][x] {
+							assert_eq!(id, ship.id);
+						}
+					}
 				}
-				_ => {}
 			}
 		}
 	}
 
-	let amount =
-		config::SHIP_ONE_BLOCK_AMOUNT + (config::SHIP_TWO_BLOCK_AMOUNT * 2) + (config::SHIP_THREE_BLOCK_AMOUNT * 3);
-	assert_eq!(ships, amount);
+	// at most ten boats fit on a 9x4 board when every boat needs a gap all around
+	let fleet = [ShipClass::new("Patrol boat", 1, 11)];
+	assert!(set_ships(Board::new(9, 4), &fleet, Adjacency::Any, &mut rng).is_ok());
+	assert!(set_ships(Board::new(9, 4), &fleet, Adjacency::NoEdge, &mut rng).is_ok());
+	assert_eq!(set_ships(Board::new(9, 4), &fleet, Adjacency::NoEdgeOrCorner, &mut rng), Err(FleetError::NoLayout));
+	assert!(
+		set_ships(Board::new(9, 4), &[ShipClass::new("Patrol boat", 1, 10)], Adjacency::NoEdgeOrCorner, &mut rng).is_ok()
+	);
 }
 
-pub struct Attack {
-	history: Vec<(usize, usize, game::HitType)>,
-	todo: Vec<(usize, usize)>,
+#[test]
+fn set_ships_picks_layouts_evenly() {
+	// two ships of one cell on a board of four cells can be arranged in six ways
+	let mut rng = GameRng::seed_from_u64(0);
+	let mut counts = std::collections::HashMap::new();
+	for _ in 0..6000 {
+		let board = set_ships(Board::new(4, 1), &[ShipClass::new("Patrol boat", 1, 2)], Adjacency::Any, &mut rng).unwrap();
+		let layout: Vec<bool> = board[0].iter().map(|cell| matches!(cell, Cell::Ship(_))).collect();
+		*counts.entry(layout).or_insert(0) += 1;
+	}
+
+	assert_eq!(counts.len(), 6);
+	for count in counts.values() {
+		assert!((800..1200).contains(count), "{:?}", counts);
+	}
 }
 
-impl Attack {
-	pub fn new() -> Self {
+/// What a strategy gets to see of the board it shoots at, the ships are hidden until they are sunk
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+	/// The shots fired so far, `Cell::Shot` is a miss and `Cell::Damage` a hit, every other cell is `Cell::Empty`
+	pub board: Board,
+	/// The ships that were sunk so far
+	pub sunk: Vec<PlacedShip>,
+	/// The whole fleet of the game
+	pub fleet: Vec<ShipClass>,
+	pub adjacency: Adjacency,
+}
+
+impl Observation {
+	/// Look at the shots on `board_shots` that were fired at the ships on `board_ships`
+	pub fn new(board_shots: &Board, board_ships: &Board, rules: &Rules) -> Self {
+		let mut board = Board::new(board_shots.size_x(), board_shots.size_y());
+		for (pos_y, row) in board_shots.rows().enumerate() {
+			for (pos_x, cell) in row.iter().enumerate() {
+				board[pos_y][pos_x] = match cell {
+					Cell::Shot => Cell::Shot,
+					Cell::Damage => Cell::Damage,
+					_ => Cell::Empty,
+				};
+			}
+		}
+
 		Self {
-			history: vec![(0, 0, game::HitType::Miss), (0, 0, game::HitType::Miss)],
-			todo: vec![],
+			board,
+			sunk: board_ships.fleet().get_ships().iter().filter(|ship| ship.is_sunk()).cloned().collect(),
+			fleet: rules.fleet.clone(),
+			adjacency: rules.adjacency,
 		}
 	}
 
-	pub fn shoot(&mut self, board: &config::Board) -> (usize, usize) {
-		let mut pos_x: usize = 0;
-		let mut pos_y: usize = 0;
+	/// The ships that are still afloat
+	pub fn get_afloat(&self) -> Vec<ShipClass> {
+		self
+			.fleet
+			.iter()
+			.map(|kind| {
+				let sunk = self.sunk.iter().filter(|ship| ship.class.name == kind.name).count();
+				ShipClass::new(&kind.name, kind.size, kind.amount.saturating_sub(sunk))
+			})
+			.filter(|kind| kind.amount > 0)
+			.collect()
+	}
 
-		if !self.todo.is_empty() {
-			let (x, y) = self.todo.pop().unwrap();
-			pos_x = x;
-			pos_y = y;
-		} else {
-			let mut valid_shot = false;
+	/// Whether a cell has not been shot at yet
+	pub fn is_open(&self, pos_x: usize, pos_y: usize) -> bool {
+		self.board[pos_y][pos_x] == Cell::Empty
+	}
 
-			while !valid_shot {
-				pos_x = rand::thread_rng().gen_range(0..config::SIZE_X);
-				pos_y = rand::thread_rng().gen_range(0..config::SIZE_Y);
+	/// What the shot at these coordinates did
+	pub fn get_hit_type(&self, pos_x: usize, pos_y: usize) -> HitType {
+		match self.board[pos_y][pos_x] {
+			Cell::Damage if self.sunk.iter().any(|ship| ship.contains(pos_x, pos_y)) => HitType::HitNSunk,
+			Cell::Damage => HitType::Hit,
+			_ => HitType::Miss,
+		}
+	}
+}
 
-				if board[pos_y][pos_x] != Cell::Shot && board[pos_y][pos_x] != Cell::Damage {
-					valid_shot = true;
-				}
-			}
+/// What a strategy weighs for its next shot, to see why it shoots where it does
+#[derive(Clone, Debug, PartialEq)]
+pub enum Insight {
+	/// A score for every cell by row, the shot goes to one of the cells with the highest score
+	Scores(Vec<Vec<f64>>),
+	/// Groups of cells in the order they are tried, the shot goes to a random cell of the first group
+	Queue(Vec<Vec<(usize, usize)>>),
+}
+
+/// How a computer player places its fleet and picks where to shoot
+pub trait Strategy {
+	/// Place the whole fleet on an empty board the way the [`Placement`] in the rules likes it unless the strategy
+	/// knows better
+	fn place_fleet(&mut self, rules: &Rules, rng: &mut GameRng) -> Result<Board, FleetError> {
+		placement::place_fleet(rules, rng)
+	}
+
+	/// The next cell to shoot at, it must not have been shot at yet
+	fn next_shot(&mut self, observation: &Observation, rng: &mut GameRng) -> (usize, usize);
+
+	/// What the strategy weighs for its next shot without touching the random numbers of the game, none when it can't
+	/// tell
+	fn explain(&self, _observation: &Observation) -> Option<Insight> {
+		None
+	}
+
+	/// Why the strategy gave up, once this is set the other side wins without another shot being fired
+	fn get_forfeit(&self) -> Option<String> {
+		None
+	}
+
+	/// The game is over and `won` tells whether this strategy sank the other fleet or the other side forfeited
+	fn game_over(&mut self, _won: bool) {}
+}
+
+/// How well the AI plays
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Difficulty {
+	/// Shoots at random
+	Easy,
+	/// Hunts at random and shoots around its hits
+	#[default]
+	Normal,
+	/// Hunts on a checkerboard spaced by the smallest ship afloat and shoots around its hits
+	Parity,
+	/// Shoots where the most placements of the ships still afloat meet and hunts on the checkerboard
+	Hard,
+	/// Shoots where a ship is most likely to be given every arrangement of the fleet that is left
+	Expert,
+	/// Shoots like the hard AI but learns from my past games where I put my ships and where I look first
+	Adaptive,
+}
+
+impl Difficulty {
+	/// Every difficulty from the weakest to the strongest followed by the one that learns
+	pub const ALL: [Difficulty; 6] = [
+		Difficulty::Easy,
+		Difficulty::Normal,
+		Difficulty::Parity,
+		Difficulty::Hard,
+		Difficulty::Expert,
+		Difficulty::Adaptive,
+	];
+
+	/// The name a difficulty goes by on the command line and in reports
+	pub fn name(&self) -> &'static str {
+		match self {
+			Difficulty::Easy => "easy",
+			Difficulty::Normal => "normal",
+			Difficulty::Parity => "parity",
+			Difficulty::Hard => "hard",
+			Difficulty::Expert => "expert",
+			Difficulty::Adaptive => "adaptive",
 		}
+	}
+}
 
-		self.history.push((pos_x, pos_y, game::get_hit_type(board, board, pos_x, pos_y)));
+/// The strategy the AI plays with at a difficulty, the adaptive one learns from the past games in the profile
+pub fn get_strategy(difficulty: Difficulty, profile: &Profile) -> Box<dyn Strategy> {
+	match difficulty {
+		Difficulty::Easy => Box::new(RandomAttack),
+		Difficulty::Normal => Box::new(Attack::new()),
+		Difficulty::Parity => Box::new(Attack::with_parity()),
+		Difficulty::Hard => Box::new(DensityAttack::with_parity()),
+		Difficulty::Expert => Box::new(SolverAttack::new(config::SOLVER_BUDGET)),
+		Difficulty::Adaptive => Box::new(AdaptiveAttack::new(profile.clone())),
+	}
+}
+
+/// The targeting of the easy AI, every shot goes to a random cell
+pub struct RandomAttack;
 
-		(pos_x, pos_y)
+impl Strategy for RandomAttack {
+	fn next_shot(&mut self, observation: &Observation, rng: &mut GameRng) -> (usize, usize) {
+		get_random_cell(observation, get_open_cells(observation), rng)
 	}
 
-	pub fn shoot_after_hit(&mut self, board: &config::Board) -> (usize, usize) {
-		let mut possible_shots: Vec<[usize; 2]> = vec![];
+	fn explain(&self, observation: &Observation) -> Option<Insight> {
+		Some(Insight::Queue(vec![get_open_cells(observation)]))
+	}
+}
 
-		let (last_x, last_y, _) = &self.history[self.history.len() - 1];
-		let (before_x, before_y, before_hit) = &self.history[self.history.len() - 2];
+/// The targeting of the hard AI, every shot goes to a cell with the highest [`Density`]
+#[derive(Default)]
+pub struct DensityAttack {
+	parity: bool,
+}
 
-		let max_x = config::SIZE_X - 2;
-		let max_y = config::SIZE_Y - 2;
+impl Strategy for DensityAttack {
+	fn next_shot(&mut self, observation: &Observation, rng: &mut GameRng) -> (usize, usize) {
+		let density = Density::new(observation);
+		get_random_cell(observation, get_density_targets(observation, &density, self.parity), rng)
+	}
 
-		// we know last_hit was a HitType::Hit so we check if there is a direction already apparent
-		if before_hit == &game::HitType::Hit {
-			if *before_x == *last_x {
-				if *last_y < max_y && board[*last_y + 1][*last_x] != Cell::Shot && board[*last_y + 1][*last_x] != Cell::Damage {
-					possible_shots.push([*last_x, *last_y + 1]);
-				}
-				if *last_y > 0 && board[*last_y - 1][*last_x] != Cell::Shot && board[*last_y - 1][*last_x] != Cell::Damage {
-					possible_shots.push([*last_x, *last_y - 1]);
-				}
-				if *before_y < max_y
-					&& board[*before_y + 1][*last_x] != Cell::Shot
-					&& board[*before_y + 1][*last_x] != Cell::Damage
-				{
-					possible_shots.push([*last_x, *before_y + 1]);
-				}
-				if *before_y > 0 && board[*before_y - 1][*last_x] != Cell::Shot && board[*before_y - 1][*last_x] != Cell::Damage
-				{
-					possible_shots.push([*last_x, *before_y - 1]);
-				}
-			} else {
-				if *last_x < max_x && board[*last_y][*last_x + 1] != Cell::Shot && board[*last_y][*last_x + 1] != Cell::Damage {
-					possible_shots.push([*last_x + 1, *last_y]);
-				}
-				if *last_x > 0 && board[*last_y][*last_x - 1] != Cell::Shot && board[*last_y][*last_x - 1] != Cell::Damage {
-					possible_shots.push([*last_x - 1, *last_y]);
-				}
-				if *before_x < max_x
-					&& board[*last_y][*before_x + 1] != Cell::Shot
-					&& board[*last_y][*before_x + 1] != Cell::Damage
-				{
-					possible_shots.push([*before_x + 1, *last_y]);
-				}
-				if *before_x > 0 && board[*last_y][*before_x - 1] != Cell::Shot && board[*last_y][*before_x - 1] != Cell::Damage
-				{
-					possible_shots.push([*before_x - 1, *last_y]);
-				}
-			}
-		} else {
-			if *last_x < max_x && board[*last_y][*last_x + 1] != Cell::Shot && board[*last_y][*last_x + 1] != Cell::Damage {
-				possible_shots.push([*last_x + 1, *last_y]);
+	fn explain(&self, observation: &Observation) -> Option<Insight> {
+		let density = Density::new(observation);
+		Some(Insight::Scores(get_density_scores(observation, &density, self.parity)))
+	}
+}
+
+impl DensityAttack {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Hunts only on the checkerboard of the smallest ship afloat
+	pub fn with_parity() -> Self {
+		Self { parity: true }
+	}
+}
+
+/// The adaptive AI, it shoots like the hard AI but counts the placements I used in past games more and keeps its own
+/// fleet away from the cells I used to shoot at first
+pub struct AdaptiveAttack {
+	profile: Profile,
+}
+
+impl Strategy for AdaptiveAttack {
+	// the layout I would find last out of a few random ones, unless the rules ask for another placement
+	fn place_fleet(&mut self, rules: &Rules, rng: &mut GameRng) -> Result<Board, FleetError> {
+		if rules.placement != Placement::Random {
+			return placement::place_fleet(rules, rng);
+		}
+
+		let habits = self.profile.get_habits(rules.size_x, rules.size_y, &rules.fleet);
+		let get_looks = |board: &Board| -> u64 {
+			let ships = board.fleet().get_ships();
+			ships.iter().flat_map(|ship| &ship.cells).map(|(pos_x, pos_y)| habits.get_looks(*pos_x, *pos_y)).sum()
+		};
+
+		let mut best = set_ships(Board::new(rules.size_x, rules.size_y), &rules.fleet, rules.adjacency, rng)?;
+		if habits.games() == 0 {
+			return Ok(best);
+		}
+
+		for _ in 1..config::ADAPTIVE_LAYOUTS {
+			let board = set_ships(Board::new(rules.size_x, rules.size_y), &rules.fleet, rules.adjacency, rng)?;
+			if get_looks(&board) < get_looks(&best) {
+				best = board;
 			}
-			if *last_x > 0 && board[*last_y][*last_x - 1] != Cell::Shot && board[*last_y][*last_x - 1] != Cell::Damage {
-				possible_shots.push([*last_x - 1, *last_y]);
+		}
+
+		Ok(best)
+	}
+
+	fn next_shot(&mut self, observation: &Observation, rng: &mut GameRng) -> (usize, usize) {
+		let density = self.get_density(observation);
+		get_random_cell(observation, get_density_targets(observation, &density, true), rng)
+	}
+
+	fn explain(&self, observation: &Observation) -> Option<Insight> {
+		let density = self.get_density(observation);
+		Some(Insight::Scores(get_density_scores(observation, &density, true)))
+	}
+}
+
+impl AdaptiveAttack {
+	/// Learns from the games in the profile, without any it plays like the hard AI
+	pub fn new(profile: Profile) -> Self {
+		Self { profile }
+	}
+
+	fn get_density(&self, observation: &Observation) -> Density {
+		let board = &observation.board;
+		let habits = self.profile.get_habits(board.size_x(), board.size_y(), &observation.fleet);
+
+		Density::with_prior(observation, |kind, cells| habits.get_prior(kind, cells))
+	}
+}
+
+/// The targeting of the expert AI, every shot goes to a cell with the highest [`solver::Posterior`] probability
+///
+/// When no arrangement turns up within the budget it shoots like the hard AI. Sampling draws from numbers of its own
+/// seeded from the game when the fleet is placed, and the last posterior is kept, so explaining a shot neither changes
+/// it nor solves the board twice.
+pub struct SolverAttack {
+	budget: solver::Budget,
+	// none until the game hands out a seed, the explanation can't tell what the shot would weigh before that
+	sampler: RefCell<Option<GameRng>>,
+	last: RefCell<Option<(Observation, solver::Posterior)>>,
+}
+
+impl Strategy for SolverAttack {
+	fn place_fleet(&mut self, rules: &Rules, rng: &mut GameRng) -> Result<Board, FleetError> {
+		*self.sampler.get_mut() = Some(GameRng::seed_from_u64(rng.gen()));
+		placement::place_fleet(rules, rng)
+	}
+
+	fn next_shot(&mut self, observation: &Observation, rng: &mut GameRng) -> (usize, usize) {
+		// a fleet placed some other way leaves the seed to the first shot
+		self.sampler.get_mut().get_or_insert_with(|| GameRng::seed_from_u64(rng.gen()));
+
+		match self.get_posterior(observation) {
+			Some(posterior) if posterior.arrangements() > 0 => {
+				get_random_cell(observation, posterior.get_best(observation), rng)
 			}
-			if *last_y < max_y && board[*last_y + 1][*last_x] != Cell::Shot && board[*last_y + 1][*last_x] != Cell::Damage {
-				possible_shots.push([*last_x, *last_y + 1]);
+			_ => DensityAttack::with_parity().next_shot(observation, rng),
+		}
+	}
+
+	fn explain(&self, observation: &Observation) -> Option<Insight> {
+		let posterior = self.get_posterior(observation)?;
+		if posterior.arrangements() == 0 {
+			return DensityAttack::with_parity().explain(observation);
+		}
+
+		Some(Insight::Scores(get_scores(observation, |pos_x, pos_y| posterior.get(pos_x, pos_y))))
+	}
+}
+
+impl SolverAttack {
+	/// Does up to `budget` work for every shot
+	pub fn new(budget: solver::Budget) -> Self {
+		Self {
+			budget,
+			sampler: RefCell::new(None),
+			last: RefCell::new(None),
+		}
+	}
+
+	// the posterior of the last observation is reused, whether the shot or its explanation asked first
+	fn get_posterior(&self, observation: &Observation) -> Option<solver::Posterior> {
+		let mut last = self.last.borrow_mut();
+		if let Some((seen, posterior)) = last.as_ref() {
+			if seen == observation {
+				return Some(posterior.clone());
 			}
-			if *last_y > 0 && board[*last_y - 1][*last_x] != Cell::Shot && board[*last_y - 1][*last_x] != Cell::Damage {
-				possible_shots.push([*last_x, *last_y - 1]);
+		}
+
+		let posterior = solver::solve(observation, &self.budget, self.sampler.borrow_mut().as_mut()?);
+		*last = Some((observation.clone(), posterior.clone()));
+
+		Some(posterior)
+	}
+}
+
+/// A guess at a ship that was hit but is not sunk yet, `axis` is known once two hits line up
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wound {
+	pub cells: Vec<(usize, usize)>,
+	pub axis: Option<Rotation>,
+}
+
+/// The targeting of the normal AI, it hunts at random and shoots around every ship it hit until each hit belongs to
+/// a sunk ship
+#[derive(Default)]
+pub struct Attack {
+	parity: bool,
+}
+
+impl Strategy for Attack {
+	fn next_shot(&mut self, observation: &Observation, rng: &mut GameRng) -> (usize, usize) {
+		let wounds = get_wounds(observation);
+
+		for wound in &wounds {
+			let targets = get_wound_targets(observation, wound);
+			if !targets.is_empty() {
+				return get_random_cell(observation, targets, rng);
 			}
 		}
 
-		let (next_x, next_y) = if !possible_shots.is_empty() {
-			let index = rand::thread_rng().gen_range(0..possible_shots.len());
-			(possible_shots[index][0], possible_shots[index][1])
+		get_random_cell(observation, self.get_hunt_cells(observation), rng)
+	}
+
+	fn explain(&self, observation: &Observation) -> Option<Insight> {
+		let mut queue: Vec<Vec<(usize, usize)>> = get_wounds(observation)
+			.iter()
+			.map(|wound| get_wound_targets(observation, wound))
+			.filter(|targets| !targets.is_empty())
+			.collect();
+		queue.push(self.get_hunt_cells(observation));
+
+		Some(Insight::Queue(queue))
+	}
+}
+
+impl Attack {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Hunts only on the checkerboard of the smallest ship afloat
+	pub fn with_parity() -> Self {
+		Self { parity: true }
+	}
+
+	fn get_hunt_cells(&self, observation: &Observation) -> Vec<(usize, usize)> {
+		if self.parity {
+			get_parity_cells(observation)
 		} else {
-			self.shoot(board)
+			get_open_cells(observation)
+		}
+	}
+}
+
+// the hits that no sunk ship explains, every straight run of them is a ship we know the axis of and every hit outside
+// of a run a ship we don't, runs come first and the longest of them first
+fn get_wounds(observation: &Observation) -> Vec<Wound> {
+	let board = &observation.board;
+	let is_hit = |pos_x: usize, pos_y: usize| {
+		board[pos_y][pos_x] == Cell::Damage && !observation.sunk.iter().any(|ship| ship.contains(pos_x, pos_y))
+	};
+
+	let mut wounds: Vec<Wound> = Vec::new();
+	for rotation in [Rotation::Horizontal, Rotation::Vertical] {
+		let (lines, length) = match rotation {
+			Rotation::Horizontal => (board.size_y(), board.size_x()),
+			Rotation::Vertical => (board.size_x(), board.size_y()),
 		};
 
-		self.history.push((next_x, next_y, game::get_hit_type(board, board, next_x, next_y)));
+		for line in 0..lines {
+			let mut run: Vec<(usize, usize)> = Vec::new();
+			for step in 0..=length {
+				let cell = match rotation {
+					Rotation::Horizontal => (step, line),
+					Rotation::Vertical => (line, step),
+				};
 
-		if game::get_hit_type(board, board, next_x, next_y) == game::HitType::Miss {
-			for coords in &possible_shots {
-				self.todo.push((coords[0], coords[1]));
+				if step < length && is_hit(cell.0, cell.1) {
+					run.push(cell);
+				} else if run.len() > 1 {
+					wounds.push(Wound {
+						cells: std::mem::take(&mut run),
+						axis: Some(rotation),
+					});
+				} else {
+					run.clear();
+				}
 			}
 		}
+	}
+	wounds.sort_by_key(|wound| std::cmp::Reverse(wound.cells.len()));
+
+	for pos_y in 0..board.size_y() {
+		for pos_x in 0..board.size_x() {
+			if is_hit(pos_x, pos_y) && !wounds.iter().any(|wound| wound.cells.contains(&(pos_x, pos_y))) {
+				wounds.push(Wound {
+					cells: vec![(pos_x, pos_y)],
+					axis: None,
+				});
+			}
+		}
+	}
+
+	wounds
+}
+
+// the open cells that could continue a wounded ship, a run is extended at both ends until it is as long as the
+// biggest ship afloat, after that or when both ends are closed its hits have to belong to ships lying across it
+fn get_wound_targets(observation: &Observation, wound: &Wound) -> Vec<(usize, usize)> {
+	let board = &observation.board;
+	let water: Vec<(usize, usize)> = observation
+		.sunk
+		.iter()
+		.flat_map(|ship| movement::get_surroundings(board, &ship.cells, observation.adjacency))
+		.collect();
+	let get_target = |pos_x: usize, pos_y: usize, offset_x: isize, offset_y: isize| {
+		let x = pos_x.checked_add_signed(offset_x).filter(|x| *x < board.size_x())?;
+		let y = pos_y.checked_add_signed(offset_y).filter(|y| *y < board.size_y())?;
+
+		Some((x, y)).filter(|cell| observation.is_open(cell.0, cell.1) && !water.contains(cell))
+	};
+	let largest = observation.get_afloat().iter().map(|kind| kind.size).max().unwrap_or(0);
+
+	let (along, across): ((isize, isize), &[(isize, isize)]) = match wound.axis {
+		Some(Rotation::Horizontal) => ((1, 0), &[(0, -1), (0, 1)]),
+		Some(Rotation::Vertical) => ((0, 1), &[(-1, 0), (1, 0)]),
+		None => ((0, 0), &[(-1, 0), (1, 0), (0, -1), (0, 1)]),
+	};
+
+	let (first_x, first_y) = wound.cells[0];
+	let (last_x, last_y) = wound.cells[wound.cells.len() - 1];
+	let ends: Vec<(usize, usize)> = [
+		get_target(first_x, first_y, -along.0, -along.1),
+		get_target(last_x, last_y, along.0, along.1),
+	]
+	.into_iter()
+	.flatten()
+	.collect();
+	if wound.axis.is_some() && wound.cells.len() < largest && !ends.is_empty() {
+		return ends;
+	}
+
+	let mut targets: Vec<(usize, usize)> = Vec::new();
+	for (pos_x, pos_y) in &wound.cells {
+		for (offset_x, offset_y) in across {
+			if let Some(cell) = get_target(*pos_x, *pos_y, *offset_x, *offset_y) {
+				if !targets.contains(&cell) {
+					targets.push(cell);
+				}
+			}
+		}
+	}
+
+	targets
+}
+
+fn get_open_cells(observation: &Observation) -> Vec<(usize, usize)> {
+	let board = &observation.board;
+
+	(0..board.size_y())
+		.flat_map(|pos_y| (0..board.size_x()).map(move |pos_x| (pos_x, pos_y)))
+		.filter(|(pos_x, pos_y)| observation.is_open(*pos_x, *pos_y))
+		.collect()
+}
+
+// every ship afloat covers at least one cell of each diagonal stripe spaced by the length of the smallest ship, the
+// stripe with the fewest open cells left is the quickest to search and follows the smallest ship as ships sink
+fn get_parity_cells(observation: &Observation) -> Vec<(usize, usize)> {
+	let open = get_open_cells(observation);
+	let spacing = match observation.get_afloat().iter().map(|kind| kind.size).min() {
+		Some(size) if size > 1 => size,
+		_ => return open,
+	};
 
-		(next_x, next_y)
+	(0..spacing)
+		.map(|stripe| open.iter().filter(|(pos_x, pos_y)| (pos_x + pos_y) % spacing == stripe).copied().collect::<Vec<_>>())
+		.min_by_key(|cells| cells.len())
+		.unwrap_or(open)
+}
+
+// the cells with the highest density, while hunting with parity only the cells on the checkerboard are worth a shot
+fn get_density_targets(observation: &Observation, density: &Density, parity: bool) -> Vec<(usize, usize)> {
+	if parity && get_wounds(observation).is_empty() {
+		density.get_best_of(&get_parity_cells(observation))
+	} else {
+		density.get_best(observation)
 	}
 }
 
+// the density of every cell a shot could go to
+fn get_density_scores(observation: &Observation, density: &Density, parity: bool) -> Vec<Vec<f64>> {
+	let hunt = if parity && get_wounds(observation).is_empty() {
+		Some(get_parity_cells(observation))
+	} else {
+		None
+	};
+
+	get_scores(observation, |pos_x, pos_y| match &hunt {
+		Some(cells) if !cells.contains(&(pos_x, pos_y)) => 0.0,
+		_ => density.get(pos_x, pos_y) as f64,
+	})
+}
+
+// a score for every cell by row, the cells that were shot at score nothing
+fn get_scores(observation: &Observation, score: impl Fn(usize, usize) -> f64) -> Vec<Vec<f64>> {
+	let board = &observation.board;
+
+	(0..board.size_y())
+		.map(|pos_y| {
+			(0..board.size_x())
+				.map(|pos_x| {
+					if observation.is_open(pos_x, pos_y) {
+						score(pos_x, pos_y)
+					} else {
+						0.0
+					}
+				})
+				.collect()
+		})
+		.collect()
+}
+
+// any open cell will do when there are no candidates left
+fn get_random_cell(observation: &Observation, candidates: Vec<(usize, usize)>, rng: &mut GameRng) -> (usize, usize) {
+	let candidates = if candidates.is_empty() {
+		get_open_cells(observation)
+	} else {
+		candidates
+	};
+
+	candidates.choose(rng).copied().unwrap_or((0, 0))
+}
+
+#[cfg(test)]
+use crate::notation;
+
+// a fraction of the expert's usual budget to keep the tests quick
+#[cfg(test)]
+const TEST_BUDGET: solver::Budget = solver::Budget {
+	nodes: 100_000,
+	samples: 5_000,
+	time: None,
+};
+
+#[cfg(test)]
+fn observe(board: &Board) -> Observation {
+	Observation::new(board, board, &Rules::default())
+}
+
+#[test]
+fn observation_works() {
+	let mut board = notation::parse("AA.o\n.xB.\n..b.\n\nA Destroyer\nB Destroyer\n").unwrap();
+	board.fleet_mut().record_hit(2, 1);
+	board[1][2] = Cell::Damage;
+	let rules = Rules {
+		fleet: vec![ShipClass::new("Destroyer", 2, 3)],
+		..Rules::default()
+	};
+
+	let observation = Observation::new(&board, &board, &rules);
+	assert_eq!(notation::format(&observation.board), "...o\n.xx.\n..x.\n");
+	assert_eq!(observation.sunk.len(), 1);
+	assert_eq!(observation.get_afloat(), vec![ShipClass::new("Destroyer", 2, 2)]);
+	assert_eq!(observation.get_hit_type(2, 2), HitType::HitNSunk);
+	assert_eq!(observation.get_hit_type(1, 1), HitType::Hit);
+	assert_eq!(observation.get_hit_type(3, 0), HitType::Miss);
+	assert!(observation.is_open(0, 0));
+	assert!(!observation.is_open(3, 0));
+}
+
+#[cfg(test)]
+fn get_attack_targets(text: &str) -> Vec<(usize, usize)> {
+	let observation = observe(&notation::parse(text).unwrap());
+	let mut attack = Attack::new();
+	let mut rng = GameRng::seed_from_u64(0);
+
+	let mut targets: Vec<(usize, usize)> = (0..200).map(|_| attack.next_shot(&observation, &mut rng)).collect();
+	targets.sort_by_key(|(pos_x, pos_y)| (*pos_y, *pos_x));
+	targets.dedup();
+	targets
+}
+
 #[test]
 fn attack_works() {
+	// a single hit could continue in any direction
+	assert_eq!(get_attack_targets("oooo\noxoo\no.oo\n"), vec![(1, 2)]);
+	assert_eq!(get_attack_targets("....\n.x..\n....\n"), vec![(1, 0), (0, 1), (2, 1), (1, 2)]);
+
+	// two hits in a row are extended at both ends
+	assert_eq!(get_attack_targets("......\n.xx...\n......\n"), vec![(0, 1), (3, 1)]);
+	assert_eq!(get_attack_targets("......\noxx...\n......\n"), vec![(3, 1)]);
+
+	// when both ends are closed or the run is longer than any ship the hits belong to ships lying across
+	assert_eq!(get_attack_targets("......\noxxo..\n......\n"), vec![(1, 0), (2, 0), (1, 2), (2, 2)]);
+	assert_eq!(get_attack_targets("......\n.xxx..\n......\n"), vec![(1, 0), (2, 0), (3, 0), (1, 2), (2, 2), (3, 2)]);
+}
+
+#[test]
+fn attack_tracks_several_wounded_ships() {
+	// the hit next to the sunk destroyer is still unexplained
+	let mut board = notation::parse("aax...\n......\n\nA Destroyer\n").unwrap();
+	let observation = observe(&board);
+	assert_eq!(
+		get_wounds(&observation),
+		vec![Wound {
+			cells: vec![(2, 0)],
+			axis: None
+		}]
+	);
+	assert_eq!(get_attack_targets(&notation::format(&board)), vec![(3, 0), (2, 1)]);
+
+	// two ships hit around a corner give a run in each direction
+	board = notation::parse(".x...\n.xx..\n.....\n").unwrap();
+	assert_eq!(
+		get_wounds(&observe(&board)),
+		vec![
+			Wound {
+				cells: vec![(1, 1), (2, 1)],
+				axis: Some(Rotation::Horizontal)
+			},
+			Wound {
+				cells: vec![(1, 0), (1, 1)],
+				axis: Some(Rotation::Vertical)
+			},
+		]
+	);
+
+	// when every run is stuck the lone hits are followed up
+	board = notation::parse("oxxo.\n.....\n...x.\n").unwrap();
+	let observation = observe(&board);
+	assert_eq!(get_wounds(&observation).len(), 2);
+	assert_eq!(get_attack_targets(&notation::format(&board)), vec![(1, 1), (2, 1)]);
+}
+
+#[test]
+fn strategies_explain_their_shots() {
+	let observation = observe(&notation::parse("......\n.x....\n......\n......\n").unwrap());
+	let mut rng = GameRng::seed_from_u64(0);
+
+	// the normal AI shoots around its hit before it hunts again
 	let mut attack = Attack::new();
-	let mut board = [[Cell::Shot; config::SIZE_X]; config::SIZE_Y];
+	let queue = match attack.explain(&observation) {
+		Some(Insight::Queue(queue)) => queue,
+		insight => panic!("Unexpected insight {:?}", insight),
+	};
+	assert_eq!(queue.len(), 2);
+	assert_eq!(queue[0], vec![(0, 1), (2, 1), (1, 0), (1, 2)]);
+	assert!(queue[0].contains(&attack.next_shot(&observation, &mut rng)));
 
-	board[5][5] = Cell::ShipThree([5, 5, 5, 4, 5, 3]);
-	assert_eq!(attack.shoot(&board), (5, 5));
-	board[5][5] = Cell::Damage;
+	// the smarter AIs shoot at a cell with the highest score
+	let strategies: [Box<dyn Strategy>; 2] = [
+		Box::new(DensityAttack::with_parity()),
+		Box::new(SolverAttack::new(TEST_BUDGET)),
+	];
+	for mut strategy in strategies {
+		// the expert draws the seed of its samples while it places its fleet
+		strategy.place_fleet(&Rules::default(), &mut rng).unwrap();
+		let scores = match strategy.explain(&observation) {
+			Some(Insight::Scores(scores)) => scores,
+			insight => panic!("Unexpected insight {:?}", insight),
+		};
+		assert_eq!(scores[1][1], 0.0);
 
-	board[4][5] = Cell::ShipThree([5, 5, 5, 4, 5, 3]);
-	assert_eq!(attack.shoot(&board), (5, 4));
-	board[4][5] = Cell::Damage;
+		let max = scores.iter().flatten().copied().fold(0.0, f64::max);
+		let (pos_x, pos_y) = strategy.next_shot(&observation, &mut rng);
+		assert_eq!(scores[pos_y][pos_x], max);
+	}
+}
+
+#[test]
+fn adaptive_attack_learns_my_habits() {
+	let rules = Rules {
+		fleet: vec![ShipClass::new("Destroyer", 2, 1)],
+		..Rules::default()
+	};
+	let board_me = movement::place_ship(Board::default(), 0, 0, &rules.fleet[0], &Rotation::Vertical);
+	let mut profile = Profile::default();
+	for _ in 0..5 {
+		profile.record(&board_me, &[(9, 9), (8, 9), (9, 8)]);
+	}
+
+	// it hunts where my destroyer always was first
+	let board_shots = Board::default();
+	let observation = Observation::new(&board_shots, &board_me, &rules);
+	let mut adaptive = AdaptiveAttack::new(profile);
+	let mut rng = GameRng::seed_from_u64(0);
+	assert_eq!(adaptive.next_shot(&observation, &mut rng), (0, 0));
+	assert_ne!(DensityAttack::with_parity().next_shot(&observation, &mut rng), (0, 0));
+
+	// its own fleet never ends up closer to where I look first than the plain random layout from the same seed
+	let get_looks = |board: &Board| {
+		board
+			.fleet()
+			.get_ships()
+			.iter()
+			.flat_map(|ship| ship.cells.clone())
+			.filter(|(pos_x, pos_y)| pos_x + pos_y >= 17)
+			.count()
+	};
+	let mut fewer = 0;
+	for seed in 0..20 {
+		let plain = set_ships(Board::default(), &rules.fleet, rules.adjacency, &mut GameRng::seed_from_u64(seed)).unwrap();
+		let learned = adaptive.place_fleet(&rules, &mut GameRng::seed_from_u64(seed)).unwrap();
+		assert!(get_looks(&learned) <= get_looks(&plain));
+		if get_looks(&learned) < get_looks(&plain) {
+			fewer += 1;
+		}
+	}
+	assert!(fewer > 0);
+}
+
+#[test]
+fn get_parity_cells_works() {
+	// the destroyers are the smallest ships of the default fleet so every other cell is enough
+	let board = notation::parse("....\n....\n").unwrap();
+	assert_eq!(get_parity_cells(&observe(&board)), vec![(0, 0), (2, 0), (1, 1), (3, 1)]);
+	let board = notation::parse(".o..\n....\n").unwrap();
+	assert_eq!(get_parity_cells(&observe(&board)), vec![(3, 0), (0, 1), (2, 1)]);
+
+	// once both destroyers are sunk the submarines only need every third cell
+	let board = notation::parse("aa....\n......\nbb....\n\nA Destroyer\nB Destroyer\n").unwrap();
+	assert_eq!(get_parity_cells(&observe(&board)), vec![(3, 0), (2, 1), (5, 1), (4, 2)]);
+}
+
+#[test]
+fn strategies_finish_a_game() {
+	let rules = Rules::default();
+
+	let mut strategies: Vec<(Difficulty, Box<dyn Strategy>)> = [
+		Difficulty::Easy,
+		Difficulty::Normal,
+		Difficulty::Parity,
+		Difficulty::Hard,
+	]
+	.into_iter()
+	.map(|difficulty| (difficulty, get_strategy(difficulty, &Profile::default())))
+	.collect();
+	strategies.push((Difficulty::Expert, Box::new(SolverAttack::new(TEST_BUDGET))));
+
+	for (difficulty, mut strategy) in strategies {
+		let mut rng = GameRng::seed_from_u64(0);
+		let mut board_ships = strategy.place_fleet(&rules, &mut rng).unwrap();
+		let mut board_shots = Board::new(rules.size_x, rules.size_y);
+
+		for _ in 0..rules.size_x * rules.size_y {
+			if board_ships.fleet().is_defeated() {
+				break;
+			}
+
+			let observation = Observation::new(&board_shots, &board_ships, &rules);
+			let (pos_x, pos_y) = strategy.next_shot(&observation, &mut rng);
+			assert_eq!(board_shots[pos_y][pos_x], Cell::Empty, "{:?} shot twice at {} {}", difficulty, pos_x, pos_y);
+			board_shots[pos_y][pos_x] = match board_ships.fleet_mut().record_hit(pos_x, pos_y) {
+				Some(_) => Cell::Damage,
+				None => Cell::Shot,
+			};
+		}
+
+		assert!(board_ships.fleet().is_defeated());
+	}
+}
+
+#[test]
+fn expert_samples_from_the_game_seed() {
+	let rules = Rules::default();
+	// too small to count the open board so the first shots come from samples
+	let budget = solver::Budget {
+		nodes: 1_000,
+		samples: 500,
+		time: None,
+	};
+	let get_shots = |seed: u64, explain: bool| {
+		let mut strategy = SolverAttack::new(budget);
+		let mut rng = GameRng::seed_from_u64(seed);
+		let mut board_ships = strategy.place_fleet(&rules, &mut rng).unwrap();
+		let mut board_shots = Board::new(rules.size_x, rules.size_y);
+		let mut shots = Vec::new();
+
+		while !board_ships.fleet().is_defeated() && shots.len() < rules.size_x * rules.size_y {
+			let observation = Observation::new(&board_shots, &board_ships, &rules);
+			if explain {
+				assert!(strategy.explain(&observation).is_some());
+			}
+			let (pos_x, pos_y) = strategy.next_shot(&observation, &mut rng);
+			board_shots[pos_y][pos_x] = match board_ships.fleet_mut().record_hit(pos_x, pos_y) {
+				Some(_) => Cell::Damage,
+				None => Cell::Shot,
+			};
+			shots.push((pos_x, pos_y));
+		}
+
+		shots
+	};
+
+	assert_eq!(get_shots(0, false), get_shots(0, true));
+
+	// the samples follow the seed of the game
+	let get_scores = |seed: u64| {
+		let mut strategy = SolverAttack::new(budget);
+		strategy.place_fleet(&rules, &mut GameRng::seed_from_u64(seed)).unwrap();
+		strategy.explain(&observe(&Board::default()))
+	};
+	assert!(get_scores(0).is_some());
+	assert_eq!(get_scores(0), get_scores(0));
+	assert_ne!(get_scores(0), get_scores(1));
+	assert_eq!(SolverAttack::new(budget).explain(&observe(&Board::default())), None);
+}
+
+#[test]
+fn strategies_repeat_themselves_from_a_seed() {
+	let rules = Rules {
+		size_x: 4,
+		size_y: 4,
+		fleet: vec![ShipClass::new("Destroyer", 2, 1)],
+		..Rules::default()
+	};
+	let get_shots = |seed: u64| {
+		let mut rng = GameRng::seed_from_u64(seed);
+		let board_ships = RandomAttack.place_fleet(&rules, &mut rng).unwrap();
+		let mut board_shots = Board::new(rules.size_x, rules.size_y);
+		(0..5)
+			.map(|_| {
+				let (pos_x, pos_y) = RandomAttack.next_shot(&Observation::new(&board_shots, &board_ships, &rules), &mut rng);
+				board_shots[pos_y][pos_x] = Cell::Shot;
+				(pos_x, pos_y)
+			})
+			.collect::<Vec<(usize, usize)>>()
+	};
 
-	board[3][5] = Cell::ShipThree([5, 5, 5, 4, 5, 3]);
-	board[4][4] = Cell::Empty;
-	board[4][6] = Cell::Empty;
-	assert_eq!(attack.shoot_after_hit(&board), (5, 3));
+	assert_eq!(get_shots(7), vec![(2, 0), (1, 1), (0, 0), (3, 2), (1, 2)]);
+	assert_eq!(get_shots(7), get_shots(7));
+	assert_ne!(get_shots(7), get_shots(8));
 }
@@ -0,0 +1,120 @@
+//! The board a game is played on
+
+use crate::config;
+use crate::ships::FleetRegistry;
+use crate::Cell;
+
+use std::ops::{Index, IndexMut};
+use std::slice::Chunks;
+
+/// A grid of cells whose size is chosen at runtime, addressed via `board[y][x]`
+///
+/// Ship cells point into the fleet registry of the board via the ships id.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Board {
+	size_x: usize,
+	size_y: usize,
+	cells: Vec<Cell>,
+	fleet: FleetRegistry,
+}
+
+impl Board {
+	/// An empty board, panics if either side is 0
+	pub fn new(size_x: usize, size_y: usize) -> Self {
+		assert!(size_x > 0 && size_y > 0, "A board needs at least one cell");
+
+		Self {
+			size_x,
+			size_y,
+			cells: vec![Cell::Empty; size_x * size_y],
+			fleet: FleetRegistry::new(),
+		}
+	}
+
+	/// The amount of columns
+	pub fn size_x(&self) -> usize {
+		self.size_x
+	}
+
+	/// The amount of rows
+	pub fn size_y(&self) -> usize {
+		self.size_y
+	}
+
+	/// Iterate over all rows from top to bottom
+	pub fn rows(&self) -> Chunks<'_, Cell> {
+		self.cells.chunks(self.size_x)
+	}
+
+	/// The ships placed on this board
+	pub fn fleet(&self) -> &FleetRegistry {
+		&self.fleet
+	}
+
+	pub fn fleet_mut(&mut self) -> &mut FleetRegistry {
+		&mut self.fleet
+	}
+}
+
+impl Default for Board {
+	fn default() -> Self {
+		Self::new(config::SIZE_X, config::SIZE_Y)
+	}
+}
+
+// board[y] returns a row so we can keep addressing cells via board[y][x]
+impl Index<usize> for Board {
+	type Output = [Cell];
+
+	fn index(&self, y: usize) -> &Self::Output {
+		&self.cells[y * self.size_x..(y + 1) * self.size_x]
+	}
+}
+
+impl IndexMut<usize> for Board {
+	fn index_mut(&mut self, y: usize) -> &mut Self::Output {
+		&mut self.cells[y * self.size_x..(y + 1) * self.size_x]
+	}
+}
+
+impl<'a> IntoIterator for &'a Board {
+	type Item = &'a [Cell];
+	type IntoIter = Chunks<'a, Cell>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.rows()
+	}
+}
+
+/// Whether a board of this size can be played in the terminal
+pub fn is_valid_size(size_x: usize, size_y: usize) -> bool {
+	(config::MIN_SIZE_X..=config::MAX_SIZE_X).contains(&size_x)
+		&& (config::MIN_SIZE_Y..=config::MAX_SIZE_Y).contains(&size_y)
+}
+
+#[test]
+fn board_works() {
+	let mut board = Board::new(12, 5);
+	assert_eq!(board.size_x(), 12);
+	assert_eq!(board.size_y(), 5);
+	assert_eq!(board.rows().len(), 5);
+
+	board[4][11] = Cell::Shot;
+	assert_eq!(board[4][11], Cell::Shot);
+	assert_eq!(board[4][10], Cell::Empty);
+	assert_eq!(board[3][11], Cell::Empty);
+
+	let shots = board.rows().flatten().filter(|cell| **cell == Cell::Shot).count();
+	assert_eq!(shots, 1);
+}
+
+#[test]
+fn is_valid_size_works() {
+	assert!(is_valid_size(10, 10));
+	assert!(is_valid_size(config::MIN_SIZE_X, config::MIN_SIZE_Y));
+	assert!(is_valid_size(config::MAX_SIZE_X, config::MAX_SIZE_Y));
+	assert!(!is_valid_size(config::MIN_SIZE_X - 1, 10));
+	assert!(!is_valid_size(10, config::MIN_SIZE_Y - 1));
+	assert!(!is_valid_size(config::MAX_SIZE_X + 1, 10));
+	assert!(!is_valid_size(10, config::MAX_SIZE_Y + 1));
+}
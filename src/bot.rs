@@ -0,0 +1,626 @@
+//! Bots in any language that play as a child process speaking a line-based protocol over stdin and stdout
+//!
+//! The game writes one request per line and waits for a reply to the requests that need one. Coordinates start at
+//! `0 0` in the top left corner, `x` counts columns and `y` rows:
+//!
+//! | Game to bot                        | Bot to game               |                                               |
+//! |------------------------------------|---------------------------|-----------------------------------------------|
+//! | `battleship 1`                     | `ready <name>`            | the handshake with the protocol version       |
+//! | `rules <width> <height> <touching>`|                           | touching is `allowed`, `corners` or `never`   |
+//! | `fleet <name>:<length>:<amount>,…` |                           | the classes of the fleet                      |
+//! | `place`                            | `fleet <x>,<y>,<h/v> …`   | one spot per ship, see below                  |
+//! | `shoot`                            | `shot <x> <y>`            | a cell that was not shot at yet               |
+//! | `result <x> <y> <miss/hit/sunk>`   |                           | what the last shot did, before the next one   |
+//! | `sunk <name> <x>,<y> …`            |                           | the ship that went down with the last shot    |
+//! | `gameover <win/loss>`              |                           | the bot should exit after this                |
+//!
+//! The spots of the fleet follow the order of the fleet request, a class with an amount of two takes the next two
+//! spots. A ship at `x,y,h` reaches to the right of its cell and one at `x,y,v` down from it.
+//!
+//! A bot that takes longer than its timeout to reply, quits or replies with something that breaks the rules forfeits
+//! the game:
+//!
+//! ```text
+//! > battleship 1
+//! < ready Sweeper
+//! > rules 10 10 allowed
+//! > fleet Destroyer:2:1,Submarine:3:1
+//! > place
+//! < fleet 0,0,h 4,4,v
+//! > shoot
+//! < shot 0 0
+//! > result 0 0 miss
+//! > shoot
+//! ```
+
+use crate::ai::{FleetError, Observation, Strategy};
+use crate::board::Board;
+use crate::engine::Rules;
+use crate::game::HitType;
+use crate::movement::{self, Adjacency};
+use crate::ships::ShipClass;
+use crate::Cell;
+use crate::GameRng;
+use crate::Rotation;
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The version of the protocol sent in the handshake
+pub const PROTOCOL_VERSION: usize = 1;
+
+/// A line the game sends to a bot
+#[derive(Clone, Debug, PartialEq)]
+pub enum Request {
+	Handshake {
+		version: usize,
+	},
+	Rules {
+		size_x: usize,
+		size_y: usize,
+		adjacency: Adjacency,
+	},
+	Fleet(Vec<ShipClass>),
+	Place,
+	Shoot,
+	Result {
+		pos_x: usize,
+		pos_y: usize,
+		hit_type: HitType,
+	},
+	Sunk {
+		name: String,
+		cells: Vec<(usize, usize)>,
+	},
+	GameOver {
+		won: bool,
+	},
+}
+
+/// A line a bot sends back to the game
+#[derive(Clone, Debug, PartialEq)]
+pub enum Reply {
+	Ready(String),
+	Fleet(Vec<(usize, usize, Rotation)>),
+	Shot { pos_x: usize, pos_y: usize },
+}
+
+/// Write a request as a line without the line break
+pub fn format_request(request: &Request) -> String {
+	match request {
+		Request::Handshake { version } => format!("battleship {}", version),
+		Request::Rules {
+			size_x,
+			size_y,
+			adjacency,
+		} => format!("rules {} {} {}", size_x, size_y, get_adjacency_name(*adjacency)),
+		Request::Fleet(fleet) => {
+			let classes: Vec<String> =
+				fleet.iter().map(|kind| format!("{}:{}:{}", kind.name, kind.size, kind.amount)).collect();
+			format!("fleet {}", classes.join(","))
+		}
+		Request::Place => String::from("place"),
+		Request::Shoot => String::from("shoot"),
+		Request::Result { pos_x, pos_y, hit_type } => {
+			let result = match hit_type {
+				HitType::Miss => "miss",
+				HitType::Hit => "hit",
+				HitType::HitNSunk => "sunk",
+			};
+			format!("result {} {} {}", pos_x, pos_y, result)
+		}
+		Request::Sunk { name, cells } => {
+			let cells: Vec<String> = cells.iter().map(|(pos_x, pos_y)| format!("{},{}", pos_x, pos_y)).collect();
+			format!("sunk {} {}", name, cells.join(" "))
+		}
+		Request::GameOver { won } => format!("gameover {}", if *won { "win" } else { "loss" }),
+	}
+}
+
+/// Read a request from a line, for bots written in Rust
+pub fn parse_request(line: &str) -> Option<Request> {
+	let (keyword, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+
+	match keyword {
+		"battleship" => Some(Request::Handshake {
+			version: rest.parse().ok()?,
+		}),
+		"rules" => match rest.split_whitespace().collect::<Vec<&str>>().as_slice() {
+			[size_x, size_y, adjacency] => Some(Request::Rules {
+				size_x: size_x.parse().ok()?,
+				size_y: size_y.parse().ok()?,
+				adjacency: parse_adjacency_name(adjacency)?,
+			}),
+			_ => None,
+		},
+		"fleet" => {
+			let mut fleet = Vec::new();
+			for item in rest.split(',') {
+				let mut parts = item.rsplitn(3, ':');
+				let amount = parts.next()?.parse().ok()?;
+				let size = parts.next()?.parse().ok()?;
+				fleet.push(ShipClass::new(parts.next()?, size, amount));
+			}
+			Some(Request::Fleet(fleet))
+		}
+		"place" => Some(Request::Place),
+		"shoot" => Some(Request::Shoot),
+		"result" => match rest.split_whitespace().collect::<Vec<&str>>().as_slice() {
+			[pos_x, pos_y, result] => Some(Request::Result {
+				pos_x: pos_x.parse().ok()?,
+				pos_y: pos_y.parse().ok()?,
+				hit_type: match *result {
+					"miss" => HitType::Miss,
+					"hit" => HitType::Hit,
+					"sunk" => HitType::HitNSunk,
+					_ => return None,
+				},
+			}),
+			_ => None,
+		},
+		// names may hold spaces so the cells are read from the end
+		"sunk" => {
+			let mut words: Vec<&str> = rest.split(' ').collect();
+			let mut cells = Vec::new();
+			while let Some(cell) = words.last().and_then(|word| parse_cell(word)) {
+				cells.insert(0, cell);
+				words.pop();
+			}
+			Some(Request::Sunk {
+				name: words.join(" "),
+				cells,
+			})
+		}
+		"gameover" => match rest {
+			"win" => Some(Request::GameOver { won: true }),
+			"loss" => Some(Request::GameOver { won: false }),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// Write a reply as a line without the line break, for bots written in Rust
+pub fn format_reply(reply: &Reply) -> String {
+	match reply {
+		Reply::Ready(name) => format!("ready {}", name),
+		Reply::Fleet(spots) => {
+			let spots: Vec<String> = spots
+				.iter()
+				.map(|(pos_x, pos_y, rotation)| {
+					let rotation = match rotation {
+						Rotation::Horizontal => "h",
+						Rotation::Vertical => "v",
+					};
+					format!("{},{},{}", pos_x, pos_y, rotation)
+				})
+				.collect();
+			format!("fleet {}", spots.join(" "))
+		}
+		Reply::Shot { pos_x, pos_y } => format!("shot {} {}", pos_x, pos_y),
+	}
+}
+
+/// Read a reply from a line
+pub fn parse_reply(line: &str) -> Option<Reply> {
+	let (keyword, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+
+	match keyword {
+		"ready" => Some(Reply::Ready(String::from(rest.trim()))),
+		"fleet" => {
+			let mut spots = Vec::new();
+			for spot in rest.split_whitespace() {
+				match spot.split(',').collect::<Vec<&str>>().as_slice() {
+					[pos_x, pos_y, rotation] => spots.push((
+						pos_x.parse().ok()?,
+						pos_y.parse().ok()?,
+						match *rotation {
+							"h" => Rotation::Horizontal,
+							"v" => Rotation::Vertical,
+							_ => return None,
+						},
+					)),
+					_ => return None,
+				}
+			}
+			Some(Reply::Fleet(spots))
+		}
+		"shot" => match rest.split_whitespace().collect::<Vec<&str>>().as_slice() {
+			[pos_x, pos_y] => Some(Reply::Shot {
+				pos_x: pos_x.parse().ok()?,
+				pos_y: pos_y.parse().ok()?,
+			}),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+fn get_adjacency_name(adjacency: Adjacency) -> &'static str {
+	match adjacency {
+		Adjacency::Any => "allowed",
+		Adjacency::NoEdge => "corners",
+		Adjacency::NoEdgeOrCorner => "never",
+	}
+}
+
+fn parse_adjacency_name(name: &str) -> Option<Adjacency> {
+	match name {
+		"allowed" => Some(Adjacency::Any),
+		"corners" => Some(Adjacency::NoEdge),
+		"never" => Some(Adjacency::NoEdgeOrCorner),
+		_ => None,
+	}
+}
+
+fn parse_cell(word: &str) -> Option<(usize, usize)> {
+	let (pos_x, pos_y) = word.split_once(',')?;
+	Some((pos_x.parse().ok()?, pos_y.parse().ok()?))
+}
+
+// the running bot, its output is read on a thread of its own so we can stop waiting for it
+struct Process {
+	child: Child,
+	stdin: ChildStdin,
+	lines: Receiver<String>,
+}
+
+/// A [`Strategy`] that hands every decision to a bot running as a child process
+///
+/// The bot is started through `sh -c` with the first request and stopped once the game is over or it forfeits.
+pub struct Bot {
+	command: String,
+	timeout: Duration,
+	process: Option<Process>,
+	name: Option<String>,
+	forfeit: Option<String>,
+	last_shot: Option<(usize, usize)>,
+	sunk: usize,
+}
+
+impl Bot {
+	/// A bot started with this shell command that has `timeout` to answer every request
+	pub fn new(command: &str, timeout: Duration) -> Self {
+		Self {
+			command: String::from(command),
+			timeout,
+			process: None,
+			name: None,
+			forfeit: None,
+			last_shot: None,
+			sunk: 0,
+		}
+	}
+
+	/// The name the bot gave itself in the handshake
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+
+	fn start(&mut self, rules: &Rules) -> Result<(), String> {
+		if self.process.is_some() {
+			return Ok(());
+		}
+
+		let mut child = Command::new("sh")
+			.arg("-c")
+			.arg(&self.command)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::null())
+			.spawn()
+			.map_err(|error| format!("It could not be started: {}", error))?;
+		let stdin = child.stdin.take().ok_or("It has no input")?;
+		let stdout = child.stdout.take().ok_or("It has no output")?;
+
+		let (sender, lines) = mpsc::channel();
+		thread::spawn(move || {
+			for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+				if sender.send(line).is_err() {
+					break;
+				}
+			}
+		});
+		self.process = Some(Process { child, stdin, lines });
+
+		self.send(&Request::Handshake {
+			version: PROTOCOL_VERSION,
+		})?;
+		match self.receive()? {
+			Reply::Ready(name) => self.name = Some(name),
+			reply => return Err(format!("It answered the handshake with \"{}\"", format_reply(&reply))),
+		}
+		self.send(&Request::Rules {
+			size_x: rules.size_x,
+			size_y: rules.size_y,
+			adjacency: rules.adjacency,
+		})?;
+		self.send(&Request::Fleet(rules.fleet.clone()))
+	}
+
+	fn send(&mut self, request: &Request) -> Result<(), String> {
+		let process = self.process.as_mut().ok_or("It is not running")?;
+
+		// a bot that quit is noticed when we wait for its answer, after the lines it sent before quitting
+		let _ = writeln!(process.stdin, "{}", format_request(request)).and_then(|_| process.stdin.flush());
+
+		Ok(())
+	}
+
+	fn receive(&mut self) -> Result<Reply, String> {
+		let process = self.process.as_mut().ok_or("It is not running")?;
+
+		match process.lines.recv_timeout(self.timeout) {
+			Ok(line) => parse_reply(&line).ok_or_else(|| format!("It sent \"{}\" which is not part of the protocol", line)),
+			Err(RecvTimeoutError::Timeout) => Err(format!("It took longer than {}ms to answer", self.timeout.as_millis())),
+			Err(RecvTimeoutError::Disconnected) => Err(String::from("It quit")),
+		}
+	}
+
+	fn place(&mut self, rules: &Rules) -> Result<Board, String> {
+		self.send(&Request::Place)?;
+		let spots = match self.receive()? {
+			Reply::Fleet(spots) => spots,
+			reply => return Err(format!("It answered place with \"{}\"", format_reply(&reply))),
+		};
+
+		let ships: Vec<&ShipClass> = rules.fleet.iter().flat_map(|kind| std::iter::repeat_n(kind, kind.amount)).collect();
+		if spots.len() != ships.len() {
+			return Err(format!("It placed {} ships instead of {}", spots.len(), ships.len()));
+		}
+
+		let mut board = Board::new(rules.size_x, rules.size_y);
+		for (kind, (pos_x, pos_y, rotation)) in ships.into_iter().zip(spots) {
+			movement::check_placement(&board, pos_x as isize, pos_y as isize, kind.size, &rotation, rules.adjacency)
+				.map_err(|_| format!("It can't place its {} at {},{}", kind.name, pos_x, pos_y))?;
+			board = movement::place_ship(board, pos_x, pos_y, kind, &rotation);
+		}
+
+		Ok(board)
+	}
+
+	fn shoot(&mut self, observation: &Observation) -> Result<(usize, usize), String> {
+		let board = &observation.board;
+		self.start(&Rules {
+			size_x: board.size_x(),
+			size_y: board.size_y(),
+			fleet: observation.fleet.clone(),
+			adjacency: observation.adjacency,
+			..Rules::default()
+		})?;
+
+		// the result of the last shot shows on the board we get to see now
+		if let Some((pos_x, pos_y)) = self.last_shot {
+			let hit_type = match board[pos_y][pos_x] {
+				Cell::Damage if observation.sunk.len() > self.sunk => HitType::HitNSunk,
+				Cell::Damage => HitType::Hit,
+				_ => HitType::Miss,
+			};
+			self.send(&Request::Result { pos_x, pos_y, hit_type })?;
+			for ship in &observation.sunk[self.sunk..] {
+				self.send(&Request::Sunk {
+					name: ship.class.name.clone(),
+					cells: ship.cells.clone(),
+				})?;
+			}
+			self.sunk = observation.sunk.len();
+		}
+
+		self.send(&Request::Shoot)?;
+		match self.receive()? {
+			Reply::Shot { pos_x, pos_y }
+				if pos_x < board.size_x() && pos_y < board.size_y() && observation.is_open(pos_x, pos_y) =>
+			{
+				self.last_shot = Some((pos_x, pos_y));
+				Ok((pos_x, pos_y))
+			}
+			Reply::Shot { pos_x, pos_y } => Err(format!("It shot at {} {} which can't be shot at", pos_x, pos_y)),
+			reply => Err(format!("It answered shoot with \"{}\"", format_reply(&reply))),
+		}
+	}
+
+	fn give_up(&mut self, reason: String) {
+		self.forfeit = Some(reason);
+		self.stop(Duration::ZERO);
+	}
+
+	// give the bot a moment to exit on its own once its input is closed before it is killed
+	fn stop(&mut self, grace: Duration) {
+		if let Some(Process { mut child, stdin, .. }) = self.process.take() {
+			drop(stdin);
+			let started = Instant::now();
+			while started.elapsed() < grace && matches!(child.try_wait(), Ok(None)) {
+				thread::sleep(Duration::from_millis(10));
+			}
+			let _ = child.kill();
+			let _ = child.wait();
+		}
+	}
+}
+
+impl Strategy for Bot {
+	fn place_fleet(&mut self, rules: &Rules, _rng: &mut GameRng) -> Result<Board, FleetError> {
+		let result = self.start(rules).and_then(|_| self.place(rules));
+
+		result.map_err(|reason| {
+			self.give_up(reason.clone());
+			FleetError::Forfeit(reason)
+		})
+	}
+
+	fn next_shot(&mut self, observation: &Observation, _rng: &mut GameRng) -> (usize, usize) {
+		if self.forfeit.is_some() {
+			return (0, 0);
+		}
+
+		self.shoot(observation).unwrap_or_else(|reason| {
+			self.give_up(reason);
+			(0, 0)
+		})
+	}
+
+	fn get_forfeit(&self) -> Option<String> {
+		self.forfeit.clone()
+	}
+
+	fn game_over(&mut self, won: bool) {
+		if self.forfeit.is_none() && self.process.is_some() {
+			let _ = self.send(&Request::GameOver { won });
+			self.stop(self.timeout);
+		}
+	}
+}
+
+impl Drop for Bot {
+	fn drop(&mut self) {
+		self.stop(Duration::ZERO);
+	}
+}
+
+#[cfg(test)]
+use crate::engine::{Command as GameCommand, CommandError, Game, Phase};
+#[cfg(test)]
+use crate::{ai, config, history::Actor, notation, profile::Profile};
+#[cfg(test)]
+use rand::SeedableRng;
+
+// a bot in plain sh that places its ships in the top rows and sweeps the board row by row
+#[cfg(test)]
+const SWEEPER: &str = r#"
+	x=0; y=0
+	while read -r keyword rest; do
+		case "$keyword" in
+			battleship) echo "ready Sweeper" ;;
+			place) echo "fleet 0,0,h 0,2,h" ;;
+			shoot)
+				echo "shot $x $y"
+				x=$((x + 1))
+				if [ "$x" -ge 10 ]; then x=0; y=$((y + 1)); fi ;;
+			gameover) exit 0 ;;
+		esac
+	done
+"#;
+
+#[cfg(test)]
+fn get_rules() -> Rules {
+	Rules {
+		fleet: vec![ShipClass::new("Destroyer", 2, 1), ShipClass::new("Submarine", 3, 1)],
+		seed: Some(1),
+		..Rules::default()
+	}
+}
+
+#[test]
+fn requests_round_trip() {
+	let requests = vec![
+		Request::Handshake { version: 1 },
+		Request::Rules {
+			size_x: 12,
+			size_y: 8,
+			adjacency: Adjacency::NoEdge,
+		},
+		Request::Fleet(vec![ShipClass::new("Patrol boat", 2, 2), ShipClass::new("Carrier", 5, 1)]),
+		Request::Place,
+		Request::Shoot,
+		Request::Result {
+			pos_x: 3,
+			pos_y: 4,
+			hit_type: HitType::HitNSunk,
+		},
+		Request::Sunk {
+			name: String::from("Patrol boat"),
+			cells: vec![(3, 4), (3, 5)],
+		},
+		Request::GameOver { won: false },
+	];
+
+	let lines: Vec<String> = requests.iter().map(format_request).collect();
+	assert_eq!(lines[1], "rules 12 8 corners");
+	assert_eq!(lines[2], "fleet Patrol boat:2:2,Carrier:5:1");
+	assert_eq!(lines[6], "sunk Patrol boat 3,4 3,5");
+	assert_eq!(lines.iter().map(|line| parse_request(line).unwrap()).collect::<Vec<Request>>(), requests);
+	assert_eq!(parse_request("rules 12 8"), None);
+}
+
+#[test]
+fn replies_round_trip() {
+	let replies = vec![
+		Reply::Ready(String::from("Deep Blue Sea")),
+		Reply::Fleet(vec![(0, 0, Rotation::Horizontal), (4, 2, Rotation::Vertical)]),
+		Reply::Shot { pos_x: 9, pos_y: 0 },
+	];
+
+	let lines: Vec<String> = replies.iter().map(format_reply).collect();
+	assert_eq!(lines, vec!["ready Deep Blue Sea", "fleet 0,0,h 4,2,v", "shot 9 0"]);
+	assert_eq!(lines.iter().map(|line| parse_reply(line).unwrap()).collect::<Vec<Reply>>(), replies);
+	assert_eq!(parse_reply("shot 9"), None);
+	assert_eq!(parse_reply("fleet 0,0,d"), None);
+	assert_eq!(parse_reply("resign"), None);
+}
+
+#[test]
+fn bot_plays_a_game() {
+	let rules = get_rules();
+	let mut game = Game::with_strategy(rules.clone(), Box::new(Bot::new(SWEEPER, config::BOT_TIMEOUT))).unwrap();
+	assert_eq!(
+		notation::format(game.board_secret()).lines().take(3).collect::<Vec<&str>>(),
+		["AA........", "..........", "BBB......."]
+	);
+
+	// the easy AI plays my side so the sweeper always finds its targets
+	let mut me = ai::get_strategy(ai::Difficulty::Easy, &Profile::default());
+	game.place_fleet_with(me.as_mut()).unwrap();
+	game.execute(GameCommand::Confirm).unwrap();
+	while game.phase() == Phase::Battle {
+		match game.turn() {
+			Actor::Me => game.play_my_turn(me.as_mut()).map(|_| ()),
+			Actor::Ai => game.play_ai_turn().map(|_| ()),
+		}
+		.unwrap();
+	}
+
+	assert!(matches!(game.phase(), Phase::Finished(_)));
+}
+
+#[test]
+fn bot_forfeits() {
+	let rules = get_rules();
+	let forfeit = |command: &str| {
+		let mut bot = Bot::new(command, Duration::from_millis(200));
+		let error = bot.place_fleet(&rules, &mut GameRng::seed_from_u64(0)).unwrap_err();
+		assert_eq!(bot.get_forfeit().map(FleetError::Forfeit), Some(error.clone()));
+		error
+	};
+
+	assert_eq!(forfeit("exit 1"), FleetError::Forfeit(String::from("It quit")));
+	assert_eq!(forfeit("sleep 1"), FleetError::Forfeit(String::from("It took longer than 200ms to answer")));
+	assert_eq!(
+		forfeit("echo 'hello there'"),
+		FleetError::Forfeit(String::from("It sent \"hello there\" which is not part of the protocol"))
+	);
+	assert_eq!(
+		forfeit("echo ready Crowded; echo fleet 0,0,h 1,0,h"),
+		FleetError::Forfeit(String::from("It can't place its Submarine at 1,0"))
+	);
+
+	// a shot at a cell that was shot at before hands the game to the other side
+	let bot = Bot::new("echo ready Stubborn; echo fleet 0,0,h 0,2,h; yes shot 0 0", Duration::from_millis(500));
+	let mut game = Game::with_strategy(rules, Box::new(bot)).unwrap();
+	let mut me = ai::get_strategy(ai::Difficulty::Easy, &Profile::default());
+	game.place_fleet_with(me.as_mut()).unwrap();
+	game.execute(GameCommand::Confirm).unwrap();
+	while game.phase() == Phase::Battle {
+		match game.turn() {
+			Actor::Me => game.play_my_turn(me.as_mut()).map(|_| ()).unwrap(),
+			Actor::Ai => {
+				if let Err(error) = game.play_ai_turn() {
+					assert_eq!(error, CommandError::Forfeit(String::from("It shot at 0 0 which can't be shot at")));
+				}
+			}
+		}
+	}
+	assert_eq!(game.phase(), Phase::Finished(Actor::Me));
+}
@@ -0,0 +1,345 @@
+use battleship::ai::{Difficulty, FleetError};
+use battleship::board;
+use battleship::config;
+use battleship::engine::Rules;
+use battleship::movement::Adjacency;
+use battleship::placement::Placement;
+use battleship::ships::ShipClass;
+use battleship::simulate::Player;
+
+use std::env;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+	pub rules: Rules,
+	// the name of a saved layout to start the placing round with
+	pub preset: Option<String>,
+	// the shell command of a bot that plays instead of the AI
+	pub bot: Option<String>,
+	// show what the AI weighs next to my board and wait for a key before each of its shots
+	pub debug: bool,
+	// forget my past games instead of playing
+	pub reset_profile: bool,
+	// print the usage instead of playing, the other flags are not checked then
+	pub help: bool,
+	// play AI-vs-AI games without the terminal UI instead of a game against me
+	pub simulation: Option<Simulation>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Simulation {
+	pub games: usize,
+	// the player on the other side, none to shoot at the saved layouts instead
+	pub versus: Option<Player>,
+	pub json: bool,
+}
+
+impl Default for Simulation {
+	fn default() -> Self {
+		Self {
+			games: 100,
+			versus: Some(Player::Ai(Difficulty::Normal)),
+			json: false,
+		}
+	}
+}
+
+pub fn get_help() -> String {
+	format!(
+		"Battleship v{}\n\nUsage: battleship [options]\n       battleship simulate [options] [simulation options]\n\nOptions:\n  --width <{}-{}>    Columns of the board (default {})\n  --height <{}-{}>   Rows of the board (default {})\n  --fleet <fleet>   \"classic\" or a list of name:length:amount e.g. \"Carrier:5:1,Destroyer:2:2\"\n  --touching <how>  Whether ships may touch: \"allowed\", only at their \"corners\" or \"never\" (default allowed)\n  --mark-water      Mark the cells around a sunk ship as water when ships may not touch there\n  --ai <level>      How well the AI plays: \"easy\", \"normal\", \"parity\", \"hard\", \"expert\" or \"adaptive\" which learns\n                    from my past games (default normal)\n  --placement <how> Where the AI puts its ships: \"random\", along the \"edges\", away from the centre with\n                    \"no-centre\", \"spread\" out, in a \"cluster\" or \"anti-density\" where a hunter looks last\n                    (default random)\n  --reset-profile   Forget the past games the adaptive AI learns from\n  --preset <name>   Start with a layout saved with [s] while placing ships\n  --seed <number>   Replay the game with this seed, it is shown when a game ends\n  --hints <number>  How many hints [h] may show where to shoot next (default {})\n  --bot <command>   Play against a bot started with this shell command instead of the AI\n  --debug           Show what the AI weighs for its next shot next to my board and step through its shots\n  --help            Show this help\n\nSimulation options:\n  --games <number>  How many games the AI set with --ai or the bot set with --bot plays (default 100)\n  --versus <level>  The AI it plays against (default normal)\n  --versus-bot <command>\n                    A bot it plays against instead of an AI\n  --layouts         Shoot at the layouts saved with [s] instead of playing against another AI\n  --json            Report as JSON instead of a table\n",
+		config::VERSION,
+		config::MIN_SIZE_X,
+		config::MAX_SIZE_X,
+		config::SIZE_X,
+		config::MIN_SIZE_Y,
+		config::MAX_SIZE_Y,
+		config::SIZE_Y,
+		config::HINTS,
+	)
+}
+
+fn parse_fleet(value: Option<String>) -> Result<Vec<ShipClass>, String> {
+	let value = value.ok_or_else(|| String::from("The flag --fleet needs a value"))?;
+
+	if value == "classic" {
+		return Ok(config::get_classic_fleet());
+	}
+
+	let mut fleet: Vec<ShipClass> = vec![];
+	for item in value.split(',') {
+		let parts = item.split(':').map(|part| part.trim()).collect::<Vec<&str>>();
+		let kind = match parts.as_slice() {
+			[name, size, amount] if !name.is_empty() => {
+				let size = size.parse::<usize>().map_err(|_| format!("The length of \"{}\" is not a number", name))?;
+				let amount = amount.parse::<usize>().map_err(|_| format!("The amount of \"{}\" is not a number", name))?;
+				ShipClass::new(name, size, amount)
+			}
+			_ => return Err(format!("The ship \"{}\" has to be written as name:length:amount", item)),
+		};
+
+		if kind.size == 0 || kind.amount == 0 {
+			return Err(format!("The ship \"{}\" needs a length and amount of at least 1", kind.name));
+		}
+		if fleet.iter().any(|other| other.name == kind.name) {
+			return Err(format!("The ship \"{}\" is listed more than once", kind.name));
+		}
+
+		fleet.push(kind);
+	}
+
+	Ok(fleet)
+}
+
+fn parse_adjacency(value: Option<String>) -> Result<Adjacency, String> {
+	match value.as_deref() {
+		Some("allowed") => Ok(Adjacency::Any),
+		Some("corners") => Ok(Adjacency::NoEdge),
+		Some("never") => Ok(Adjacency::NoEdgeOrCorner),
+		Some(value) => Err(format!("The value \"{}\" for --touching has to be allowed, corners or never", value)),
+		None => Err(String::from("The flag --touching needs a value")),
+	}
+}
+
+fn parse_difficulty(flag: &str, value: Option<String>) -> Result<Difficulty, String> {
+	let value = value.ok_or_else(|| format!("The flag {} needs a value", flag))?;
+
+	Difficulty::ALL.into_iter().find(|difficulty| difficulty.name() == value).ok_or_else(|| {
+		format!("The value \"{}\" for {} has to be easy, normal, parity, hard, expert or adaptive", value, flag)
+	})
+}
+
+fn parse_placement(value: Option<String>) -> Result<Placement, String> {
+	let value = value.ok_or_else(|| String::from("The flag --placement needs a value"))?;
+
+	Placement::ALL.into_iter().find(|placement| placement.name() == value).ok_or_else(|| {
+		format!(
+			"The value \"{}\" for --placement has to be random, edges, no-centre, spread, cluster or anti-density",
+			value
+		)
+	})
+}
+
+fn parse_number(flag: &str, value: Option<String>) -> Result<usize, String> {
+	match value {
+		Some(value) => value.parse::<usize>().map_err(|_| format!("The value \"{}\" for {} is not a number", value, flag)),
+		None => Err(format!("The flag {} needs a value", flag)),
+	}
+}
+
+fn parse_seed(value: Option<String>) -> Result<u64, String> {
+	match value {
+		Some(value) => value.parse::<u64>().map_err(|_| format!("The value \"{}\" for --seed is not a number", value)),
+		None => Err(String::from("The flag --seed needs a value")),
+	}
+}
+
+pub fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<Options, String> {
+	let mut options = Options::default();
+	let mut args = args.peekable();
+	if args.peek().map(String::as_str) == Some("simulate") {
+		args.next();
+		options.simulation = Some(Simulation::default());
+	}
+
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--width" => options.rules.size_x = parse_number("--width", args.next())?,
+			"--height" => options.rules.size_y = parse_number("--height", args.next())?,
+			"--fleet" => options.rules.fleet = parse_fleet(args.next())?,
+			"--touching" => options.rules.adjacency = parse_adjacency(args.next())?,
+			"--mark-water" => options.rules.mark_water = true,
+			"--ai" => options.rules.difficulty = parse_difficulty("--ai", args.next())?,
+			"--placement" => options.rules.placement = parse_placement(args.next())?,
+			"--seed" => options.rules.seed = Some(parse_seed(args.next())?),
+			"--hints" => options.rules.hints = parse_number("--hints", args.next())?,
+			"--preset" => {
+				options.preset = Some(args.next().ok_or_else(|| String::from("The flag --preset needs a value"))?);
+			}
+			"--debug" => options.debug = true,
+			"--reset-profile" => options.reset_profile = true,
+			"--bot" => options.bot = Some(args.next().ok_or_else(|| String::from("The flag --bot needs a value"))?),
+			"--games" | "--versus" | "--versus-bot" | "--layouts" | "--json" => {
+				let simulation =
+					options.simulation.as_mut().ok_or_else(|| format!("The flag {} only works with battleship simulate", arg))?;
+				match arg.as_str() {
+					"--games" => simulation.games = parse_number("--games", args.next())?,
+					"--versus" => simulation.versus = Some(Player::Ai(parse_difficulty("--versus", args.next())?)),
+					"--versus-bot" => {
+						let command = args.next().ok_or_else(|| String::from("The flag --versus-bot needs a value"))?;
+						simulation.versus = Some(Player::Bot(command));
+					}
+					"--layouts" => simulation.versus = None,
+					_ => simulation.json = true,
+				}
+			}
+			"--help" | "-h" => {
+				options.help = true;
+				return Ok(options);
+			}
+			_ => return Err(format!("Unknown option \"{}\"\n\n{}", arg, get_help())),
+		}
+	}
+
+	let rules = &options.rules;
+	if !board::is_valid_size(rules.size_x, rules.size_y) {
+		return Err(format!(
+			"A board has to be between {}x{} and {}x{} but was {}x{}",
+			config::MIN_SIZE_X,
+			config::MIN_SIZE_Y,
+			config::MAX_SIZE_X,
+			config::MAX_SIZE_Y,
+			rules.size_x,
+			rules.size_y,
+		));
+	}
+
+	if rules.mark_water && rules.adjacency == Adjacency::Any {
+		return Err(String::from("The flag --mark-water needs ships that may not touch via --touching corners or never"));
+	}
+
+	let longest_side = rules.size_x.max(rules.size_y);
+	if let Some(kind) = rules.fleet.iter().find(|kind| kind.size > longest_side) {
+		return Err(format!("The {} with a length of {} does not fit on the board", kind.name, kind.size));
+	}
+
+	Ok(options)
+}
+
+// layouts are saved in the home directory so they can be used from anywhere
+pub fn get_presets_path() -> PathBuf {
+	let home = env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+	home.join(config::PRESETS_FILE)
+}
+
+// my past games are kept next to the layouts
+pub fn get_profile_path() -> PathBuf {
+	let home = env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+	home.join(config::PROFILE_FILE)
+}
+
+pub fn get_fleet_error(error: &FleetError) -> String {
+	match error {
+		FleetError::ShipTooLong(name) => format!("The {} does not fit on the board", name),
+		FleetError::NotEnoughSpace { needed, free } => {
+			format!("The fleet takes up {} cells but the board only has {}", needed, free)
+		}
+		FleetError::NoLayout => String::from("There is no way to arrange the fleet on the board"),
+		FleetError::NoRoom(name) => {
+			format!("This leaves no room for the {}, place the ship elsewhere or pick one up", name)
+		}
+		FleetError::Forfeit(reason) => format!("The opponent gave up placing its fleet. {}", reason),
+	}
+}
+
+#[test]
+fn parse_args_works() {
+	let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<String>>().into_iter();
+
+	assert_eq!(parse_args(args(&[])), Ok(Options::default()));
+	assert_eq!(
+		parse_args(args(&["--width", "20", "--height", "8"])),
+		Ok(Options {
+			rules: Rules {
+				size_x: 20,
+				size_y: 8,
+				..Rules::default()
+			},
+			preset: None,
+			bot: None,
+			debug: false,
+			reset_profile: false,
+			help: false,
+			simulation: None,
+		})
+	);
+	assert!(parse_args(args(&["--width"])).is_err());
+	assert!(parse_args(args(&["--width", "ten"])).is_err());
+	assert!(parse_args(args(&["--width", "36"])).is_err());
+	assert!(parse_args(args(&["--height", "27"])).is_err());
+	assert!(parse_args(args(&["--size", "10"])).is_err());
+
+	assert_eq!(parse_args(args(&["--touching", "allowed"])).unwrap().rules.adjacency, Adjacency::Any);
+	assert_eq!(parse_args(args(&["--touching", "corners"])).unwrap().rules.adjacency, Adjacency::NoEdge);
+	assert_eq!(parse_args(args(&["--touching", "never"])).unwrap().rules.adjacency, Adjacency::NoEdgeOrCorner);
+	assert!(parse_args(args(&["--touching", "sometimes"])).is_err());
+	assert!(parse_args(args(&["--touching"])).is_err());
+	assert!(parse_args(args(&["--touching", "never", "--mark-water"])).unwrap().rules.mark_water);
+	assert!(parse_args(args(&["--mark-water"])).is_err());
+	assert_eq!(parse_args(args(&["--ai", "easy"])).unwrap().rules.difficulty, Difficulty::Easy);
+	assert_eq!(parse_args(args(&["--ai", "parity"])).unwrap().rules.difficulty, Difficulty::Parity);
+	assert_eq!(parse_args(args(&["--ai", "hard"])).unwrap().rules.difficulty, Difficulty::Hard);
+	assert_eq!(parse_args(args(&["--ai", "expert"])).unwrap().rules.difficulty, Difficulty::Expert);
+	assert_eq!(parse_args(args(&["--ai", "adaptive"])).unwrap().rules.difficulty, Difficulty::Adaptive);
+	assert!(parse_args(args(&["--reset-profile"])).unwrap().reset_profile);
+	assert_eq!(parse_args(args(&["--placement", "no-centre"])).unwrap().rules.placement, Placement::NoCentre);
+	assert_eq!(parse_args(args(&["--placement", "anti-density"])).unwrap().rules.placement, Placement::AntiDensity);
+	assert!(parse_args(args(&["--placement", "hidden"])).is_err());
+	assert!(parse_args(args(&["--placement"])).is_err());
+	assert!(parse_args(args(&["--ai", "cheating"])).is_err());
+	assert_eq!(parse_args(args(&["--preset", "Corner camp"])).unwrap().preset, Some(String::from("Corner camp")));
+	assert!(parse_args(args(&["--preset"])).is_err());
+	assert_eq!(parse_args(args(&["--seed", "42"])).unwrap().rules.seed, Some(42));
+	assert!(parse_args(args(&["--seed", "-1"])).is_err());
+	assert_eq!(parse_args(args(&["--hints", "0"])).unwrap().rules.hints, 0);
+	assert!(parse_args(args(&["--hints"])).is_err());
+	assert_eq!(parse_args(args(&["--bot", "python3 bot.py"])).unwrap().bot, Some(String::from("python3 bot.py")));
+	assert!(parse_args(args(&["--bot"])).is_err());
+	assert!(parse_args(args(&["--debug"])).unwrap().debug);
+	assert!(parse_args(args(&["--help"])).unwrap().help);
+	assert!(parse_args(args(&["--width", "99", "-h"])).unwrap().help);
+	assert!(!parse_args(args(&["--debug"])).unwrap().help);
+}
+
+#[test]
+fn parse_simulation_works() {
+	let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<String>>().into_iter();
+
+	assert_eq!(parse_args(args(&["simulate"])).unwrap().simulation, Some(Simulation::default()));
+	assert_eq!(
+		parse_args(args(&["simulate", "--ai", "hard", "--versus", "easy", "--games", "20", "--json"])).unwrap(),
+		Options {
+			rules: Rules {
+				difficulty: Difficulty::Hard,
+				..Rules::default()
+			},
+			preset: None,
+			bot: None,
+			debug: false,
+			reset_profile: false,
+			help: false,
+			simulation: Some(Simulation {
+				games: 20,
+				versus: Some(Player::Ai(Difficulty::Easy)),
+				json: true,
+			}),
+		}
+	);
+	assert_eq!(parse_args(args(&["simulate", "--layouts"])).unwrap().simulation.unwrap().versus, None);
+	assert!(parse_args(args(&["simulate", "--versus", "cheating"])).is_err());
+	assert_eq!(
+		parse_args(args(&["simulate", "--versus-bot", "./bot"])).unwrap().simulation.unwrap().versus,
+		Some(Player::Bot(String::from("./bot")))
+	);
+	assert!(parse_args(args(&["--versus-bot", "./bot"])).is_err());
+	assert!(parse_args(args(&["--games", "20"])).is_err());
+	assert!(parse_args(args(&["--json", "simulate"])).is_err());
+}
+
+#[test]
+fn parse_fleet_works() {
+	let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<String>>().into_iter();
+
+	assert_eq!(parse_args(args(&["--fleet", "classic"])).unwrap().rules.fleet, config::get_classic_fleet());
+	assert_eq!(
+		parse_args(args(&["--fleet", "Carrier:5:1, Destroyer:2:2"])).unwrap().rules.fleet,
+		vec![ShipClass::new("Carrier", 5, 1), ShipClass::new("Destroyer", 2, 2)]
+	);
+	assert!(parse_args(args(&["--fleet"])).is_err());
+	assert!(parse_args(args(&["--fleet", "Carrier:5"])).is_err());
+	assert!(parse_args(args(&["--fleet", "Carrier:five:1"])).is_err());
+	assert!(parse_args(args(&["--fleet", "Carrier:0:1"])).is_err());
+	assert!(parse_args(args(&["--fleet", "Carrier:5:1,Carrier:4:1"])).is_err());
+	assert!(parse_args(args(&["--fleet", "Giant:11:1"])).is_err());
+	assert!(parse_args(args(&["--width", "12", "--fleet", "Giant:11:1"])).is_ok());
+}
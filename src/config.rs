@@ -1,15 +1,21 @@
-use crate::Cell;
-use crate::Ship;
+//! Defaults and limits of the game
+
+use crate::ships::ShipClass;
+use crate::solver::Budget;
+use std::time::Duration;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 // BOARD SIZE
-// MUST BE >8 <=35
+// the default size can be changed at game start via --width and --height
 pub const SIZE_X: usize = 10;
-// MUST BE >3 <=26
 pub const SIZE_Y: usize = 10;
-pub type BoardRow = [Cell; SIZE_X];
-pub type Board = [BoardRow; SIZE_Y];
+// MUST BE >8 <=35
+pub const MIN_SIZE_X: usize = 9;
+pub const MAX_SIZE_X: usize = 35;
+// MUST BE >3 <=26
+pub const MIN_SIZE_Y: usize = 4;
+pub const MAX_SIZE_Y: usize = 26;
 
 // GUI
 pub const EMPTY: &str = "░░░";
@@ -17,16 +23,68 @@ pub const SHIP: &str = "▓▓▓";
 pub const CROSSHAIR: &str = " ◎ ";
 pub const SHOT: &str = " ◌ ";
 pub const DAMAGE: &str = " ╳ ";
+pub const HINT: &str = " ◆ ";
 
 // SHIPS
-pub fn get_entitie_size(kind: &Ship) -> usize {
-	match kind {
-		Ship::OneBlock => 1,
-		Ship::TwoBlock => 2,
-		Ship::ThreeBlock => 3,
-	}
+/// The default fleet, ships are placed in the order of this list
+pub fn get_fleet() -> Vec<ShipClass> {
+	vec![ShipClass::new("Destroyer", 2, 2), ShipClass::new("Submarine", 3, 2)]
 }
 
-pub const SHIP_ONE_BLOCK_AMOUNT: usize = 3;
-pub const SHIP_TWO_BLOCK_AMOUNT: usize = 2;
-pub const SHIP_THREE_BLOCK_AMOUNT: usize = 1;
+/// The 5-4-3-3-2 fleet of the classic board game
+pub fn get_classic_fleet() -> Vec<ShipClass> {
+	vec![
+		ShipClass::new("Carrier", 5, 1),
+		ShipClass::new("Battleship", 4, 1),
+		ShipClass::new("Cruiser", 3, 1),
+		ShipClass::new("Submarine", 3, 1),
+		ShipClass::new("Destroyer", 2, 1),
+	]
+}
+
+/// The file in the home directory our fleet presets are saved to
+pub const PRESETS_FILE: &str = ".battleship_presets";
+
+/// The longest name a preset can be saved under
+pub const PRESET_NAME_LENGTH: usize = 30;
+
+/// How much more a placement of a ship counts for the hard AI for every hit it explains
+pub const DENSITY_HIT_WEIGHT: u64 = 20;
+
+/// How many hints I may ask for in a game unless --hints says otherwise
+pub const HINTS: usize = 3;
+
+/// How much the expert AI may think about a shot, a fraction of a second on a recent machine
+pub const SOLVER_BUDGET: Budget = Budget {
+	nodes: 3_000_000,
+	samples: 150_000,
+	time: None,
+};
+
+/// How long a bot may take to answer a request before it forfeits
+pub const BOT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The file in the home directory the adaptive AI remembers my past games in
+pub const PROFILE_FILE: &str = ".battleship_profile";
+
+/// How many of my latest games the profile keeps
+pub const PROFILE_GAMES: usize = 50;
+
+/// How many of my first shots in a game the profile keeps
+pub const PROFILE_SHOTS: usize = 10;
+
+/// How many times more the adaptive AI counts a placement I always use than one I never used
+pub const PROFILE_WEIGHT: u64 = 4;
+
+/// How many random layouts the adaptive AI compares to keep its fleet away from where I look first
+pub const ADAPTIVE_LAYOUTS: usize = 20;
+
+/// How many random layouts the AI compares to find the one that suits its placement best
+pub const PLACEMENT_LAYOUTS: usize = 50;
+
+/// How many random layouts are tried before the placer falls back to searching all of them
+pub const PLACEMENT_SAMPLES: usize = 1000;
+
+pub fn get_entitie_size(kind: &ShipClass) -> usize {
+	kind.size
+}
@@ -0,0 +1,187 @@
+//! Probability density of where the ships still afloat can be
+
+use crate::ai::{self, Observation};
+use crate::config;
+use crate::movement;
+use crate::ships::ShipClass;
+use crate::Cell;
+
+/// For every cell the number of legal placements of the ships still afloat that cover it
+///
+/// Placements never cover a miss or a sunk ship and follow the adjacency rule. Every hit that has not been explained
+/// by a sunk ship multiplies the weight of the placements covering it by [`config::DENSITY_HIT_WEIGHT`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Density {
+	size_x: usize,
+	size_y: usize,
+	scores: Vec<u64>,
+}
+
+impl Density {
+	pub fn new(observation: &Observation) -> Self {
+		Self::with_prior(observation, |_, _| 1)
+	}
+
+	/// A density where every placement also counts as often as the prior says, like a placement the other side favours
+	pub fn with_prior(observation: &Observation, prior: impl Fn(&ShipClass, &[(usize, usize)]) -> u64) -> Self {
+		let board = &observation.board;
+		let (size_x, size_y) = (board.size_x(), board.size_y());
+		let index = |pos_x: usize, pos_y: usize| pos_y * size_x + pos_x;
+
+		// sunk ships and the water the adjacency rule leaves around them can't hold another ship
+		let mut blocked: Vec<bool> = board.rows().flatten().map(|cell| *cell == Cell::Shot).collect();
+		for ship in &observation.sunk {
+			let surroundings = movement::get_surroundings(board, &ship.cells, observation.adjacency);
+			for (pos_x, pos_y) in ship.cells.iter().chain(&surroundings) {
+				blocked[index(*pos_x, *pos_y)] = true;
+			}
+		}
+		let is_hit = |pos_x: usize, pos_y: usize| board[pos_y][pos_x] == Cell::Damage && !blocked[index(pos_x, pos_y)];
+
+		let mut scores = vec![0u64; size_x * size_y];
+		for kind in observation.get_afloat() {
+			for (pos_x, pos_y, rotation) in ai::get_candidates(board, kind.size) {
+				let cells = movement::get_coordinates(pos_x, pos_y, kind.size, &rotation);
+				if cells.iter().any(|(x, y)| blocked[index(*x, *y)]) {
+					continue;
+				}
+
+				// a hit right next to the ship would belong to another ship that touches it
+				let surroundings = movement::get_surroundings(board, &cells, observation.adjacency);
+				if surroundings.iter().any(|(x, y)| is_hit(*x, *y)) {
+					continue;
+				}
+
+				let hits = cells.iter().filter(|(x, y)| is_hit(*x, *y)).count() as u32;
+				// long ships over many hits outgrow any count, they all end up at the top instead
+				let weight = (kind.amount as u64)
+					.saturating_mul(config::DENSITY_HIT_WEIGHT.saturating_pow(hits))
+					.saturating_mul(prior(&kind, &cells));
+				for (x, y) in cells {
+					scores[index(x, y)] = scores[index(x, y)].saturating_add(weight);
+				}
+			}
+		}
+
+		Self { size_x, size_y, scores }
+	}
+
+	/// The score of a cell
+	pub fn get(&self, pos_x: usize, pos_y: usize) -> u64 {
+		self.scores[pos_y * self.size_x + pos_x]
+	}
+
+	/// The highest score of any cell that was not shot at yet
+	pub fn get_max(&self, observation: &Observation) -> u64 {
+		self.get_open(observation).map(|(pos_x, pos_y)| self.get(pos_x, pos_y)).max().unwrap_or(0)
+	}
+
+	/// All cells that were not shot at yet and share the highest score
+	pub fn get_best(&self, observation: &Observation) -> Vec<(usize, usize)> {
+		self.get_best_of(&self.get_open(observation).collect::<Vec<_>>())
+	}
+
+	/// The cells out of these that share the highest score among them
+	pub fn get_best_of(&self, cells: &[(usize, usize)]) -> Vec<(usize, usize)> {
+		let max = cells.iter().map(|(pos_x, pos_y)| self.get(*pos_x, *pos_y)).max().unwrap_or(0);
+
+		cells.iter().filter(|(pos_x, pos_y)| self.get(*pos_x, *pos_y) == max).copied().collect()
+	}
+
+	fn get_open<'a>(&'a self, observation: &'a Observation) -> impl Iterator<Item = (usize, usize)> + 'a {
+		(0..self.size_y)
+			.flat_map(move |pos_y| (0..self.size_x).map(move |pos_x| (pos_x, pos_y)))
+			.filter(move |(pos_x, pos_y)| observation.is_open(*pos_x, *pos_y))
+	}
+}
+
+#[cfg(test)]
+use crate::{engine::Rules, movement::Adjacency, notation};
+
+#[cfg(test)]
+fn observe(text: &str, fleet: Vec<ShipClass>, adjacency: Adjacency) -> Observation {
+	let board = notation::parse(text).unwrap();
+	let rules = Rules {
+		fleet,
+		adjacency,
+		..Rules::default()
+	};
+
+	Observation::new(&board, &board, &rules)
+}
+
+#[cfg(test)]
+fn get_row(density: &Density, pos_y: usize) -> Vec<u64> {
+	(0..density.size_x).map(|pos_x| density.get(pos_x, pos_y)).collect()
+}
+
+#[test]
+fn density_works() {
+	let destroyer = vec![ShipClass::new("Destroyer", 2, 1)];
+
+	let observation = observe("....\n", destroyer.clone(), Adjacency::Any);
+	let density = Density::new(&observation);
+	assert_eq!(get_row(&density, 0), vec![1, 2, 2, 1]);
+	assert_eq!(density.get_best(&observation), vec![(1, 0), (2, 0)]);
+
+	let observation = observe(".o..\n", destroyer.clone(), Adjacency::Any);
+	assert_eq!(get_row(&Density::new(&observation), 0), vec![0, 0, 1, 1]);
+
+	// two of a class count twice
+	let observation = observe("....\n", vec![ShipClass::new("Destroyer", 2, 2)], Adjacency::Any);
+	assert_eq!(get_row(&Density::new(&observation), 0), vec![2, 4, 4, 2]);
+}
+
+#[test]
+fn density_follows_hits() {
+	let observation = observe(".....\n..x..\n.....\n", vec![ShipClass::new("Cruiser", 3, 1)], Adjacency::Any);
+	let density = Density::new(&observation);
+	// the cruiser standing upright through the hit adds to the hit and the cells around it
+	assert_eq!(get_row(&density, 1), vec![21, 41, 80, 41, 21]);
+	assert_eq!(density.get_best(&observation), vec![(1, 1), (3, 1)]);
+}
+
+#[test]
+fn density_saturates_on_many_hits() {
+	// a ship of a house fleet lying over twenty hits weighs more than a u64 can hold
+	let text = format!("{}.....\n", "x".repeat(20));
+	let observation = observe(&text, vec![ShipClass::new("Longboat", 21, 1)], Adjacency::Any);
+	let density = Density::new(&observation);
+	assert_eq!(density.get(20, 0), u64::MAX);
+	assert_eq!(density.get_max(&observation), u64::MAX);
+	assert!(density.get_best(&observation).contains(&(20, 0)));
+}
+
+#[test]
+fn density_saturates_with_a_prior() {
+	// twelve hits are enough once a placement also counts thousands of times
+	let text = format!("{}...\n", "x".repeat(12));
+	let observation = observe(&text, vec![ShipClass::new("Longboat", 13, 1)], Adjacency::Any);
+	let density = Density::with_prior(&observation, |_, _| 5_000);
+	assert_eq!(density.get(12, 0), u64::MAX);
+	assert!(density.get_best(&observation).contains(&(12, 0)));
+}
+
+#[test]
+fn density_follows_the_prior() {
+	let observation = observe("....\n", vec![ShipClass::new("Destroyer", 2, 1)], Adjacency::Any);
+	let density = Density::with_prior(&observation, |_, cells| if cells.contains(&(0, 0)) { 3 } else { 1 });
+	assert_eq!(get_row(&density, 0), vec![3, 4, 2, 1]);
+	assert_eq!(density.get_best(&observation), vec![(1, 0)]);
+}
+
+#[test]
+fn density_follows_the_adjacency_rule() {
+	// the sunk destroyer leaves water all around it and the hit rules out placements touching it
+	let text = "aa....\n......\n....x.\n\nA Destroyer\n";
+	let fleet = vec![ShipClass::new("Destroyer", 2, 2)];
+
+	let density = Density::new(&observe(text, fleet.clone(), Adjacency::NoEdgeOrCorner));
+	assert_eq!(get_row(&density, 0), vec![0, 0, 0, 1, 2, 1]);
+	assert_eq!(get_row(&density, 1), vec![0, 0, 0, 0, 20, 0]);
+	assert_eq!(get_row(&density, 2), vec![1, 2, 1, 20, 60, 20]);
+
+	let density = Density::new(&observe(text, fleet, Adjacency::Any));
+	assert_eq!(get_row(&density, 0), vec![0, 0, 2, 3, 3, 2]);
+	assert_eq!(get_row(&density, 1), vec![2, 3, 4, 4, 23, 3]);
+}
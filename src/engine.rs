@@ -0,0 +1,1148 @@
+//! The rules of a whole game as a state machine
+
+use crate::ai::{self, Difficulty, Insight, Observation, Strategy};
+use crate::board::Board;
+use crate::config;
+use crate::density::Density;
+use crate::game::{self, HitType};
+use crate::history::Actor;
+use crate::movement::{self, Adjacency, PlacementError};
+use crate::placement::Placement;
+use crate::preset::{Preset, PresetError};
+use crate::profile::Profile;
+use crate::ships::{self, PlacedShip, ShipClass, ShipId, ShipTracker};
+use crate::Cell;
+use crate::Direction;
+use crate::GameRng;
+use crate::Rotation;
+
+use rand::{Rng, SeedableRng};
+
+#[cfg(test)]
+use crate::{movement::Edge, notation};
+
+/// Everything that is decided before a game starts
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rules {
+	pub size_x: usize,
+	pub size_y: usize,
+	pub fleet: Vec<ShipClass>,
+	pub adjacency: Adjacency,
+	/// Reveal the water the adjacency rule leaves around a ship as soon as it is sunk
+	pub mark_water: bool,
+	pub difficulty: Difficulty,
+	/// How the AI arranges its fleet, apart from how well it shoots
+	pub placement: Placement,
+	/// My past games the adaptive AI learns from, the other difficulties ignore them
+	pub profile: Profile,
+	/// The seed of every random decision in the game, a fresh one is drawn when there is none
+	pub seed: Option<u64>,
+	/// How many hints I may ask for during the battle
+	pub hints: usize,
+}
+
+impl Default for Rules {
+	fn default() -> Self {
+		Self {
+			size_x: config::SIZE_X,
+			size_y: config::SIZE_Y,
+			fleet: config::get_fleet(),
+			adjacency: Adjacency::default(),
+			mark_water: false,
+			difficulty: Difficulty::default(),
+			placement: Placement::default(),
+			profile: Profile::default(),
+			seed: None,
+			hints: config::HINTS,
+		}
+	}
+}
+
+/// The phases a game goes through, a finished game records the winner
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Phase {
+	Placement,
+	Battle,
+	Finished(Actor),
+}
+
+/// Everything a player can do, placement commands act on the ship that is currently being placed
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Command {
+	Move(Direction),
+	Rotate,
+	PlaceShip,
+	ResetPlacement,
+	/// Lift a placed ship back up so it can be moved again, the ship that is currently being placed waits its turn
+	PickUp {
+		pos_x: usize,
+		pos_y: usize,
+	},
+	/// Lift the ship that was placed last back up
+	Undo,
+	/// Place the rest of the fleet at random or reroll the whole fleet once every ship is placed
+	Randomise,
+	/// Start the battle once every ship is placed
+	Confirm,
+	Fire {
+		pos_x: usize,
+		pos_y: usize,
+	},
+	/// Ask where to shoot next, this uses up one of the hints the rules allow
+	Hint,
+}
+
+/// Why a command was rejected, a rejected command leaves the game untouched
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommandError {
+	WrongPhase,
+	NotYourTurn,
+	/// The ship that is currently being placed can't go where the command would put it
+	InvalidPlacement(PlacementError),
+	/// There is no ship the command could act on
+	NoShip,
+	/// The battle can't start before every ship is placed
+	FleetIncomplete,
+	/// The rest of the fleet does not fit on the board
+	Fleet(ai::FleetError),
+	/// The preset does not fit the rules of this game
+	Preset(PresetError),
+	OutOfBounds,
+	AlreadyShot,
+	/// Every hint the rules allow was used up
+	NoHintsLeft,
+	/// The strategy that was asked to play gave up for this reason and lost the game
+	Forfeit(String),
+}
+
+/// The result of a single shot, `sunk` holds the ship that went down with it and `water` the cells around it that
+/// were marked as water because of that
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shot {
+	pub actor: Actor,
+	pub pos_x: usize,
+	pub pos_y: usize,
+	pub hit_type: HitType,
+	pub sunk: Option<PlacedShip>,
+	pub water: Vec<(usize, usize)>,
+}
+
+/// Where I should shoot next based on the misses, hits and sunk ships I have seen so far
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+	pub pos_x: usize,
+	pub pos_y: usize,
+	/// How many placements of the ships still afloat cover each cell, the hint is the cell with the most
+	pub density: Density,
+}
+
+impl Hint {
+	/// How likely a cell that was not shot at yet is to hold a ship compared to the hint, from 0 to 1
+	pub fn get_heat(&self, pos_x: usize, pos_y: usize) -> f64 {
+		match self.density.get(self.pos_x, self.pos_y) {
+			0 => 0.0,
+			max => self.density.get(pos_x, pos_y) as f64 / max as f64,
+		}
+	}
+}
+
+/// What a successful command did
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+	Moved { pos_x: usize, pos_y: usize },
+	Rotated(Rotation),
+	ShipPlaced(ShipId),
+	FleetPlaced(ShipId),
+	PlacementReset,
+	PickedUp(PlacedShip),
+	FleetRandomised,
+	PresetLoaded,
+	BattleStarted,
+	Shot(Shot),
+	Hint(Hint),
+}
+
+/// A game between me and the AI without any terminal attached
+///
+/// Front-ends translate input into [`Command`]s, run [`Game::play_ai_turn`] whenever it is the AIs turn and render
+/// the boards this exposes.
+pub struct Game {
+	rules: Rules,
+	phase: Phase,
+	turn: Actor,
+	board_me: Board,
+	board_ai: Board,
+	board_secret: Board,
+	ships: ShipTracker,
+	this_ship: Option<ShipClass>,
+	rotation: Rotation,
+	pos_x: usize,
+	pos_y: usize,
+	ai_strategy: Box<dyn Strategy>,
+	seed: u64,
+	rng: GameRng,
+	hints_used: usize,
+}
+
+impl Game {
+	/// A new game in the placement phase against the strategy of the difficulty in the rules, the AI places its fleet
+	/// right away
+	///
+	/// Fails when the fleet can't be placed on a board of this size.
+	pub fn new(rules: Rules) -> Result<Self, ai::FleetError> {
+		let strategy = ai::get_strategy(rules.difficulty, &rules.profile);
+		Self::with_strategy(rules, strategy)
+	}
+
+	/// A new game against any strategy, it places its fleet right away
+	pub fn with_strategy(rules: Rules, mut strategy: Box<dyn Strategy>) -> Result<Self, ai::FleetError> {
+		let seed = get_seed(&rules);
+		let mut rng = GameRng::seed_from_u64(seed);
+		let board_secret = strategy.place_fleet(&rules, &mut rng)?;
+		Ok(Self::create(rules, board_secret, strategy, seed, rng))
+	}
+
+	/// A new game against an already placed AI fleet
+	pub fn with_board_secret(rules: Rules, board_secret: Board) -> Self {
+		let strategy = ai::get_strategy(rules.difficulty, &rules.profile);
+		let seed = get_seed(&rules);
+		Self::create(rules, board_secret, strategy, seed, GameRng::seed_from_u64(seed))
+	}
+
+	fn create(rules: Rules, board_secret: Board, ai_strategy: Box<dyn Strategy>, seed: u64, rng: GameRng) -> Self {
+		let ships = ShipTracker::new(&rules.fleet);
+		let mut game = Self {
+			phase: Phase::Placement,
+			turn: Actor::Me,
+			board_me: Board::new(rules.size_x, rules.size_y),
+			board_ai: Board::new(rules.size_x, rules.size_y),
+			board_secret,
+			this_ship: ships.get_next_unset_ship(),
+			ships,
+			rotation: Rotation::Horizontal,
+			pos_x: 0,
+			pos_y: 0,
+			ai_strategy,
+			seed,
+			rng,
+			hints_used: 0,
+			rules,
+		};
+		game.set_start_position();
+
+		game
+	}
+
+	pub fn rules(&self) -> &Rules {
+		&self.rules
+	}
+
+	/// The seed this game was started with, the same seed and the same commands replay the same game
+	pub fn seed(&self) -> u64 {
+		self.seed
+	}
+
+	pub fn phase(&self) -> Phase {
+		self.phase
+	}
+
+	pub fn turn(&self) -> Actor {
+		self.turn
+	}
+
+	/// My ships and the shots the AI fired at them
+	pub fn board_me(&self) -> &Board {
+		&self.board_me
+	}
+
+	/// The shots I fired at the AI
+	pub fn board_ai(&self) -> &Board {
+		&self.board_ai
+	}
+
+	/// The ships of the AI
+	pub fn board_secret(&self) -> &Board {
+		&self.board_secret
+	}
+
+	/// The ship that is currently being placed
+	pub fn this_ship(&self) -> Option<&ShipClass> {
+		self.this_ship.as_ref()
+	}
+
+	pub fn rotation(&self) -> Rotation {
+		self.rotation
+	}
+
+	/// The position of the ship that is currently being placed
+	pub fn position(&self) -> (usize, usize) {
+		(self.pos_x, self.pos_y)
+	}
+
+	/// The score needed to win
+	pub fn fleet_size(&self) -> usize {
+		ships::get_fleet_size(&self.rules.fleet)
+	}
+
+	/// How many more hints I may ask for
+	pub fn hints_left(&self) -> usize {
+		self.rules.hints.saturating_sub(self.hints_used)
+	}
+
+	/// Run a command for me
+	pub fn execute(&mut self, command: Command) -> Result<Outcome, CommandError> {
+		match (self.phase, command) {
+			(Phase::Placement, Command::Move(direction)) => self.move_ship(direction),
+			(Phase::Placement, Command::Rotate) => self.rotate(),
+			(Phase::Placement, Command::PlaceShip) => self.place_ship(),
+			(Phase::Placement, Command::ResetPlacement) => Ok(self.reset_placement()),
+			(Phase::Placement, Command::PickUp { pos_x, pos_y }) => {
+				let id = self.board_me.fleet().get_at(pos_x, pos_y).map(|ship| ship.id);
+				self.pick_up(id)
+			}
+			(Phase::Placement, Command::Undo) => {
+				let id = self.board_me.fleet().get_ships().last().map(|ship| ship.id);
+				self.pick_up(id)
+			}
+			(Phase::Placement, Command::Randomise) => self.randomise(),
+			(Phase::Placement, Command::Confirm) => {
+				if self.this_ship.is_some() {
+					return Err(CommandError::FleetIncomplete);
+				}
+
+				self.phase = Phase::Battle;
+				self.turn = Actor::Me;
+
+				Ok(Outcome::BattleStarted)
+			}
+			(Phase::Battle, Command::Fire { pos_x, pos_y }) => {
+				if self.turn != Actor::Me {
+					return Err(CommandError::NotYourTurn);
+				}
+
+				self.fire(Actor::Me, pos_x, pos_y).map(Outcome::Shot)
+			}
+			(Phase::Battle, Command::Hint) => {
+				if self.turn != Actor::Me {
+					return Err(CommandError::NotYourTurn);
+				}
+
+				self.hint().map(Outcome::Hint)
+			}
+			(_, _) => Err(CommandError::WrongPhase),
+		}
+	}
+
+	/// Replace my fleet with a preset while placing ships, every ship can still be adjusted before confirming
+	pub fn load_preset(&mut self, preset: &Preset) -> Result<Outcome, CommandError> {
+		if self.phase != Phase::Placement {
+			return Err(CommandError::WrongPhase);
+		}
+
+		let board = preset.to_board(&self.rules).map_err(CommandError::Preset)?;
+		self.set_fleet(board);
+
+		Ok(Outcome::PresetLoaded)
+	}
+
+	/// Let a strategy place my fleet while placing ships, for bots and simulations that play my side
+	pub fn place_fleet_with(&mut self, strategy: &mut dyn Strategy) -> Result<Outcome, CommandError> {
+		if self.phase != Phase::Placement {
+			return Err(CommandError::WrongPhase);
+		}
+
+		let board = strategy.place_fleet(&self.rules, &mut self.rng).map_err(CommandError::Fleet)?;
+		self.set_fleet(board);
+
+		Ok(Outcome::FleetRandomised)
+	}
+
+	/// Let a strategy take my shot when it is my turn, the caller tells it when the game is over via
+	/// [`Strategy::game_over`]
+	pub fn play_my_turn(&mut self, strategy: &mut dyn Strategy) -> Result<Shot, CommandError> {
+		if self.phase != Phase::Battle {
+			return Err(CommandError::WrongPhase);
+		}
+		if self.turn != Actor::Me {
+			return Err(CommandError::NotYourTurn);
+		}
+
+		let observation = self.observe(Actor::Me);
+		let shot = strategy.next_shot(&observation, &mut self.rng);
+
+		self.fire_for(Actor::Me, shot, strategy.get_forfeit())
+	}
+
+	/// Let the AI take its shot when it is its turn
+	pub fn play_ai_turn(&mut self) -> Result<Shot, CommandError> {
+		if self.phase != Phase::Battle {
+			return Err(CommandError::WrongPhase);
+		}
+		if self.turn != Actor::Ai {
+			return Err(CommandError::NotYourTurn);
+		}
+
+		let observation = self.observe(Actor::Ai);
+		let shot = self.ai_strategy.next_shot(&observation, &mut self.rng);
+		let forfeit = self.ai_strategy.get_forfeit();
+
+		self.fire_for(Actor::Ai, shot, forfeit)
+	}
+
+	/// What the AI weighs for its next shot, this does not change the game
+	pub fn explain_ai_turn(&self) -> Option<Insight> {
+		self.ai_strategy.explain(&self.observe(Actor::Ai))
+	}
+
+	/// What a player has seen of the board they shoot at
+	pub fn observe(&self, actor: Actor) -> Observation {
+		match actor {
+			Actor::Me => Observation::new(&self.board_ai, &self.board_secret, &self.rules),
+			Actor::Ai => Observation::new(&self.board_me, &self.board_me, &self.rules),
+		}
+	}
+
+	// a position the ship does not fit at is never painted so a bad position can't reach past the board or over a ship
+	fn set_placeholder(&mut self, cell: Cell) {
+		if let Some(kind) = &self.this_ship {
+			let (pos_x, pos_y) = (self.pos_x as isize, self.pos_y as isize);
+			if movement::check_placement(&self.board_me, pos_x, pos_y, kind.size, &self.rotation, Adjacency::Any).is_ok() {
+				let board = std::mem::take(&mut self.board_me);
+				self.board_me = movement::place_entity(board, self.pos_x, self.pos_y, kind.size, &self.rotation, cell);
+			}
+		}
+	}
+
+	// the first ship starts at the first free position, turned when it only fits the other way
+	fn set_start_position(&mut self) {
+		let start = self
+			.this_ship
+			.as_ref()
+			.and_then(|kind| get_free_position(&self.board_me, kind, Rotation::Horizontal, self.rules.adjacency));
+		let (pos_x, pos_y, rotation) = start.unwrap_or((0, 0, Rotation::Horizontal));
+		self.pos_x = pos_x;
+		self.pos_y = pos_y;
+		self.rotation = rotation;
+		self.set_placeholder(Cell::Placeholder);
+	}
+
+	fn move_ship(&mut self, direction: Direction) -> Result<Outcome, CommandError> {
+		let ship_size = self.this_ship.as_ref().map_or(0, |kind| kind.size);
+		let (new_pos_x, new_pos_y) = match direction {
+			Direction::Left => (self.pos_x as isize - 1, self.pos_y as isize),
+			Direction::Right => (self.pos_x as isize + 1, self.pos_y as isize),
+			Direction::Up => (self.pos_x as isize, self.pos_y as isize - 1),
+			Direction::Down => (self.pos_x as isize, self.pos_y as isize + 1),
+		};
+		movement::check_placement(&self.board_me, new_pos_x, new_pos_y, ship_size, &self.rotation, self.rules.adjacency)
+			.map_err(CommandError::InvalidPlacement)?;
+
+		let board = std::mem::take(&mut self.board_me);
+		let (board, pos_x, pos_y) =
+			movement::move_ship(board, self.pos_x, self.pos_y, ship_size, &self.rotation, direction);
+		self.board_me = board;
+
+		self.pos_x = pos_x;
+		self.pos_y = pos_y;
+
+		Ok(Outcome::Moved { pos_x, pos_y })
+	}
+
+	fn rotate(&mut self) -> Result<Outcome, CommandError> {
+		let ship_size = self.this_ship.as_ref().map_or(0, |kind| kind.size);
+		let new_rotation = match self.rotation {
+			Rotation::Horizontal => Rotation::Vertical,
+			Rotation::Vertical => Rotation::Horizontal,
+		};
+
+		movement::check_placement(
+			&self.board_me,
+			self.pos_x as isize,
+			self.pos_y as isize,
+			ship_size,
+			&new_rotation,
+			self.rules.adjacency,
+		)
+		.map_err(CommandError::InvalidPlacement)?;
+
+		// reset previous placement and place the ship in its new rotation
+		self.set_placeholder(Cell::Empty);
+		self.rotation = new_rotation;
+		self.set_placeholder(Cell::Placeholder);
+
+		Ok(Outcome::Rotated(self.rotation))
+	}
+
+	fn place_ship(&mut self) -> Result<Outcome, CommandError> {
+		let kind = self.this_ship.clone().ok_or(CommandError::NoShip)?;
+		let (pos_x, pos_y) = (self.pos_x as isize, self.pos_y as isize);
+		movement::check_placement(&self.board_me, pos_x, pos_y, kind.size, &self.rotation, self.rules.adjacency)
+			.map_err(CommandError::InvalidPlacement)?;
+
+		let board = movement::place_ship(self.board_me.clone(), self.pos_x, self.pos_y, &kind, &self.rotation);
+		let id = match board[self.pos_y][self.pos_x] {
+			Cell::Ship(id) => id,
+			_ => unreachable!("A ship was just placed here"),
+		};
+
+		// collision detection for the new position, a ship that would leave the next one without room is rejected
+		self.ships.set_ship(&kind);
+		let next = self.ships.get_next_unset_ship();
+		let position = match &next {
+			Some(next_kind) => match get_free_position(&board, next_kind, self.rotation, self.rules.adjacency) {
+				Some(position) => Some(position),
+				None => {
+					self.ships.unset_ship(&kind);
+					return Err(CommandError::Fleet(ai::FleetError::NoRoom(next_kind.name.clone())));
+				}
+			},
+			None => None,
+		};
+		self.board_me = board;
+		self.this_ship = next;
+
+		match position {
+			Some((pos_x, pos_y, rotation)) => {
+				self.pos_x = pos_x;
+				self.pos_y = pos_y;
+				self.rotation = rotation;
+				self.set_placeholder(Cell::Placeholder);
+
+				Ok(Outcome::ShipPlaced(id))
+			}
+			// the battle only starts once the fleet is confirmed so every ship can still be adjusted
+			None => Ok(Outcome::FleetPlaced(id)),
+		}
+	}
+
+	fn reset_placement(&mut self) -> Outcome {
+		self.ships = ShipTracker::new(&self.rules.fleet);
+		self.this_ship = self.ships.get_next_unset_ship();
+		self.board_me = Board::new(self.rules.size_x, self.rules.size_y);
+		self.set_start_position();
+
+		Outcome::PlacementReset
+	}
+
+	// take over a board with the whole fleet placed on it
+	fn set_fleet(&mut self, board: Board) {
+		self.board_me = board;
+		self.ships = ShipTracker::new(&self.rules.fleet);
+		for ship in self.board_me.fleet().get_ships() {
+			self.ships.set_ship(&ship.class);
+		}
+		self.this_ship = None;
+	}
+
+	fn randomise(&mut self) -> Result<Outcome, CommandError> {
+		let (board, fleet) = match self.this_ship {
+			Some(_) => {
+				self.set_placeholder(Cell::Empty);
+				(self.board_me.clone(), self.ships.get_unset_fleet())
+			}
+			None => (Board::new(self.rules.size_x, self.rules.size_y), self.rules.fleet.clone()),
+		};
+
+		match ai::set_ships(board, &fleet, self.rules.adjacency, &mut self.rng) {
+			Ok(board) => {
+				self.set_fleet(board);
+
+				Ok(Outcome::FleetRandomised)
+			}
+			Err(error) => {
+				self.set_placeholder(Cell::Placeholder);
+				Err(CommandError::Fleet(error))
+			}
+		}
+	}
+
+	fn pick_up(&mut self, id: Option<ShipId>) -> Result<Outcome, CommandError> {
+		let ship = id.and_then(|id| self.board_me.fleet_mut().remove(id)).ok_or(CommandError::NoShip)?;
+
+		// the ship we were holding goes back to the ships that still need to be placed
+		self.set_placeholder(Cell::Empty);
+		for (pos_x, pos_y) in &ship.cells {
+			self.board_me[*pos_y][*pos_x] = Cell::Empty;
+		}
+		self.ships.unset_ship(&ship.class);
+
+		let (pos_x, pos_y) = ship.cells[0];
+		self.rotation = match ship.cells.get(1) {
+			Some((next_x, _)) if *next_x == pos_x => Rotation::Vertical,
+			_ => Rotation::Horizontal,
+		};
+		self.pos_x = pos_x;
+		self.pos_y = pos_y;
+		self.this_ship = Some(ship.class.clone());
+		self.set_placeholder(Cell::Placeholder);
+
+		Ok(Outcome::PickedUp(ship))
+	}
+
+	// a strategy that gives up or shoots where it can't loses the game right away
+	fn fire_for(
+		&mut self,
+		actor: Actor,
+		(pos_x, pos_y): (usize, usize),
+		forfeit: Option<String>,
+	) -> Result<Shot, CommandError> {
+		let reason = match forfeit {
+			Some(reason) => reason,
+			None => match self.fire(actor, pos_x, pos_y) {
+				Err(CommandError::OutOfBounds) | Err(CommandError::AlreadyShot) => {
+					format!("It shot at {} {} which can't be shot at", pos_x, pos_y)
+				}
+				result => return result,
+			},
+		};
+
+		let winner = match actor {
+			Actor::Me => Actor::Ai,
+			Actor::Ai => Actor::Me,
+		};
+		self.phase = Phase::Finished(winner);
+		self.ai_strategy.game_over(winner == Actor::Ai);
+
+		Err(CommandError::Forfeit(reason))
+	}
+
+	// the cell the hard AI would pick for me, ties go to the first cell so a hint never touches the random numbers
+	fn hint(&mut self) -> Result<Hint, CommandError> {
+		if self.hints_left() == 0 {
+			return Err(CommandError::NoHintsLeft);
+		}
+
+		let observation = self.observe(Actor::Me);
+		let density = Density::new(&observation);
+		let (pos_x, pos_y) = density.get_best(&observation)[0];
+		self.hints_used += 1;
+
+		Ok(Hint { pos_x, pos_y, density })
+	}
+
+	fn fire(&mut self, actor: Actor, pos_x: usize, pos_y: usize) -> Result<Shot, CommandError> {
+		let board_shots = match actor {
+			Actor::Me => &self.board_ai,
+			Actor::Ai => &self.board_me,
+		};
+
+		if pos_x >= board_shots.size_x() || pos_y >= board_shots.size_y() {
+			return Err(CommandError::OutOfBounds);
+		}
+		if board_shots[pos_y][pos_x] == Cell::Shot || board_shots[pos_y][pos_x] == Cell::Damage {
+			return Err(CommandError::AlreadyShot);
+		}
+
+		let hit_type = match actor {
+			Actor::Me => game::get_hit_type(&self.board_ai, &self.board_secret, pos_x, pos_y),
+			Actor::Ai => game::get_hit_type(&self.board_me, &self.board_me, pos_x, pos_y),
+		};
+
+		let board_ships = match actor {
+			Actor::Me => &mut self.board_secret,
+			Actor::Ai => &mut self.board_me,
+		};
+		if hit_type != HitType::Miss {
+			board_ships.fleet_mut().record_hit(pos_x, pos_y);
+		}
+		let sunk = game::get_sunk_ship(board_ships, pos_x, pos_y).cloned();
+		let is_defeated = board_ships.fleet().is_defeated();
+
+		let board_shots = match actor {
+			Actor::Me => &mut self.board_ai,
+			Actor::Ai => &mut self.board_me,
+		};
+		board_shots[pos_y][pos_x] = match hit_type {
+			HitType::Miss => Cell::Shot,
+			_ => Cell::Damage,
+		};
+		let water = match &sunk {
+			Some(ship) if self.rules.mark_water => game::mark_water(board_shots, ship, self.rules.adjacency),
+			_ => Vec::new(),
+		};
+
+		if is_defeated {
+			self.phase = Phase::Finished(actor);
+			self.ai_strategy.game_over(actor == Actor::Ai);
+		} else if hit_type != HitType::Hit {
+			// a hit earns another shot, a miss or a sunk ship ends the turn
+			self.turn = match actor {
+				Actor::Me => Actor::Ai,
+				Actor::Ai => Actor::Me,
+			};
+		}
+
+		Ok(Shot {
+			actor,
+			pos_x,
+			pos_y,
+			hit_type,
+			sunk,
+			water,
+		})
+	}
+}
+
+// the first free position for a ship in this rotation or else turned the other way
+fn get_free_position(
+	board: &Board,
+	kind: &ShipClass,
+	rotation: Rotation,
+	adjacency: Adjacency,
+) -> Option<(usize, usize, Rotation)> {
+	let turned = match rotation {
+		Rotation::Horizontal => Rotation::Vertical,
+		Rotation::Vertical => Rotation::Horizontal,
+	};
+
+	[rotation, turned].into_iter().find_map(|rotation| {
+		let (pos_x, pos_y) = movement::get_next_available_coordinates(board, kind.size, &rotation, adjacency)?;
+		Some((pos_x, pos_y, rotation))
+	})
+}
+
+fn get_seed(rules: &Rules) -> u64 {
+	rules.seed.unwrap_or_else(|| rand::thread_rng().gen())
+}
+
+#[cfg(test)]
+fn place_fleet(game: &mut Game) {
+	while game.this_ship().is_some() {
+		let (pos_x, pos_y) = game.position();
+		game.execute(Command::PlaceShip).unwrap();
+		assert!(matches!(game.board_me()[pos_y][pos_x], Cell::Ship(_)));
+	}
+	assert_eq!(game.execute(Command::Confirm), Ok(Outcome::BattleStarted));
+}
+
+#[test]
+fn placement_works() {
+	let mut game = Game::new(Rules::default()).unwrap();
+	assert_eq!(game.phase(), Phase::Placement);
+	assert_eq!(game.this_ship().unwrap().name, "Destroyer");
+	assert_eq!(game.board_me()[0][0], Cell::Placeholder);
+	assert_eq!(game.board_me()[0][1], Cell::Placeholder);
+
+	assert_eq!(
+		game.execute(Command::Move(Direction::Left)),
+		Err(CommandError::InvalidPlacement(PlacementError::OutOfBounds(Edge::Left)))
+	);
+	assert_eq!(game.execute(Command::Move(Direction::Down)), Ok(Outcome::Moved { pos_x: 0, pos_y: 1 }));
+	assert_eq!(game.execute(Command::Rotate), Ok(Outcome::Rotated(Rotation::Vertical)));
+	assert_eq!(game.board_me()[1][0], Cell::Placeholder);
+	assert_eq!(game.board_me()[2][0], Cell::Placeholder);
+	assert_eq!(game.board_me()[1][1], Cell::Empty);
+	assert!(matches!(game.execute(Command::PlaceShip), Ok(Outcome::ShipPlaced(_))));
+	assert_eq!(game.board_me().fleet().get_ships().len(), 1);
+	assert_eq!(game.execute(Command::Fire { pos_x: 0, pos_y: 0 }), Err(CommandError::WrongPhase));
+
+	assert_eq!(game.execute(Command::ResetPlacement), Ok(Outcome::PlacementReset));
+	assert_eq!(game.board_me().fleet().get_ships().len(), 0);
+	assert_eq!(game.rotation(), Rotation::Horizontal);
+	assert_eq!(game.position(), (0, 0));
+
+	place_fleet(&mut game);
+	assert_eq!(game.board_me().fleet().get_ships().len(), 4);
+	assert_eq!(game.phase(), Phase::Battle);
+	assert_eq!(game.execute(Command::Rotate), Err(CommandError::WrongPhase));
+}
+
+#[test]
+fn pick_up_works() {
+	let mut game = Game::new(Rules::default()).unwrap();
+	assert_eq!(game.execute(Command::Undo), Err(CommandError::NoShip));
+
+	// a destroyer on A1 and an upright one on C1
+	game.execute(Command::PlaceShip).unwrap();
+	game.execute(Command::Move(Direction::Down)).unwrap();
+	game.execute(Command::Rotate).unwrap();
+	game.execute(Command::PlaceShip).unwrap();
+	let submarine = game.this_ship().unwrap().clone();
+	assert_eq!(submarine.name, "Submarine");
+	assert_eq!(game.execute(Command::PickUp { pos_x: 9, pos_y: 9 }), Err(CommandError::NoShip));
+
+	let ship = match game.execute(Command::PickUp { pos_x: 1, pos_y: 0 }) {
+		Ok(Outcome::PickedUp(ship)) => ship,
+		outcome => panic!("Unexpected outcome {:?}", outcome),
+	};
+	assert_eq!(ship.cells, vec![(0, 0), (1, 0)]);
+	assert_eq!(game.this_ship().unwrap().name, "Destroyer");
+	assert_eq!(game.position(), (0, 0));
+	assert_eq!(game.rotation(), Rotation::Horizontal);
+	assert_eq!(game.board_me()[0][0], Cell::Placeholder);
+	assert_eq!(game.board_me()[0][1], Cell::Placeholder);
+	assert_eq!(game.board_me().fleet().get_ships().len(), 1);
+	assert_eq!(game.ships.get_ships(), vec![1, 2]);
+	assert_eq!(game.board_me().rows().flatten().filter(|cell| **cell == Cell::Placeholder).count(), 2);
+
+	// dropping it somewhere else hands back the submarine
+	game.execute(Command::Move(Direction::Right)).unwrap();
+	game.execute(Command::Move(Direction::Right)).unwrap();
+	assert!(matches!(game.execute(Command::PlaceShip), Ok(Outcome::ShipPlaced(_))));
+	assert_eq!(game.this_ship(), Some(&submarine));
+	assert_eq!(game.ships.get_ships(), vec![0, 2]);
+
+	// undo lifts the destroyer we just moved, which remembers its rotation
+	assert!(matches!(game.execute(Command::Undo), Ok(Outcome::PickedUp(_))));
+	assert_eq!(game.position(), (2, 0));
+	assert!(matches!(game.execute(Command::Undo), Ok(Outcome::PickedUp(_))));
+	assert_eq!(game.position(), (0, 2));
+	assert_eq!(game.rotation(), Rotation::Vertical);
+	assert_eq!(game.board_me().fleet().get_ships().len(), 0);
+	assert_eq!(game.board_me().rows().flatten().filter(|cell| **cell == Cell::Placeholder).count(), 2);
+
+	place_fleet(&mut game);
+	assert_eq!(game.board_me().fleet().get_ships().len(), 4);
+	assert_eq!(game.execute(Command::Undo), Err(CommandError::WrongPhase));
+}
+
+#[test]
+fn randomise_works() {
+	let mut game = Game::new(Rules::default()).unwrap();
+	game.execute(Command::PlaceShip).unwrap();
+	assert_eq!(game.execute(Command::Confirm), Err(CommandError::FleetIncomplete));
+
+	// the rest of the fleet is filled in around the ship we placed ourselves
+	assert_eq!(game.execute(Command::Randomise), Ok(Outcome::FleetRandomised));
+	assert_eq!(game.board_me().fleet().get_ships().len(), 4);
+	assert_eq!(game.board_me().fleet().get_at(0, 0).unwrap().cells, vec![(0, 0), (1, 0)]);
+	assert_eq!(game.board_me().rows().flatten().filter(|cell| **cell == Cell::Placeholder).count(), 0);
+	assert_eq!(game.this_ship(), None);
+	assert_eq!(game.execute(Command::PlaceShip), Err(CommandError::NoShip));
+	assert_eq!(game.phase(), Phase::Placement);
+
+	// ships can still be adjusted before the battle starts
+	assert!(matches!(game.execute(Command::Undo), Ok(Outcome::PickedUp(_))));
+	assert_eq!(game.execute(Command::Confirm), Err(CommandError::FleetIncomplete));
+	assert!(matches!(game.execute(Command::PlaceShip), Ok(Outcome::FleetPlaced(_))));
+
+	// once every ship is placed the whole fleet is rerolled
+	assert_eq!(game.execute(Command::Randomise), Ok(Outcome::FleetRandomised));
+	assert_eq!(game.board_me().fleet().get_ships().len(), 4);
+	assert_eq!(game.ships.get_remaining(), 0);
+	assert_eq!(game.execute(Command::Confirm), Ok(Outcome::BattleStarted));
+	assert_eq!(game.phase(), Phase::Battle);
+	assert_eq!(game.execute(Command::Randomise), Err(CommandError::WrongPhase));
+}
+
+#[test]
+fn load_preset_works() {
+	let mut game = Game::new(Rules::default()).unwrap();
+	game.execute(Command::Randomise).unwrap();
+	let preset = Preset::from_board("Random", game.board_me());
+
+	let mut game = Game::new(Rules::default()).unwrap();
+	game.execute(Command::PlaceShip).unwrap();
+	assert_eq!(game.load_preset(&preset), Ok(Outcome::PresetLoaded));
+	assert_eq!(game.board_me(), &preset.to_board(game.rules()).unwrap());
+	assert_eq!(game.this_ship(), None);
+
+	let mut broken = preset.clone();
+	broken.ships.pop();
+	assert_eq!(game.load_preset(&broken), Err(CommandError::Preset(PresetError::MissingShips)));
+	assert_eq!(game.board_me().fleet().get_ships().len(), 4);
+
+	assert!(matches!(game.execute(Command::Undo), Ok(Outcome::PickedUp(_))));
+	game.execute(Command::PlaceShip).unwrap();
+	assert_eq!(game.execute(Command::Confirm), Ok(Outcome::BattleStarted));
+	assert_eq!(game.load_preset(&preset), Err(CommandError::WrongPhase));
+}
+
+#[test]
+fn placement_follows_the_adjacency_rule() {
+	let rules = Rules {
+		adjacency: Adjacency::NoEdgeOrCorner,
+		..Rules::default()
+	};
+	let mut game = Game::new(rules).unwrap();
+	let first = match game.execute(Command::PlaceShip) {
+		Ok(Outcome::ShipPlaced(id)) => id,
+		outcome => panic!("Unexpected outcome {:?}", outcome),
+	};
+
+	// the next ship starts two rows further down and may not move next to the first one
+	assert_eq!(game.position(), (0, 2));
+	assert_eq!(
+		game.execute(Command::Move(Direction::Up)),
+		Err(CommandError::InvalidPlacement(PlacementError::Adjacent(first)))
+	);
+	assert_eq!(game.position(), (0, 2));
+}
+
+#[test]
+fn placement_leaves_room_for_the_next_ship() {
+	let rules = Rules {
+		size_x: 9,
+		size_y: 4,
+		fleet: vec![ShipClass::new("Destroyer", 2, 2), ShipClass::new("Carrier", 9, 1)],
+		..Rules::default()
+	};
+	let mut game = Game::new(rules).unwrap();
+	game.execute(Command::Rotate).unwrap();
+	for _ in 0..4 {
+		game.execute(Command::Move(Direction::Right)).unwrap();
+	}
+	assert!(matches!(game.execute(Command::PlaceShip), Ok(Outcome::ShipPlaced(_))));
+
+	// a second destroyer upright in the lower left corner blocks the last row the carrier could use
+	assert_eq!((game.position(), game.rotation()), ((0, 0), Rotation::Vertical));
+	game.execute(Command::Move(Direction::Down)).unwrap();
+	game.execute(Command::Move(Direction::Down)).unwrap();
+	let board = game.board_me().clone();
+	assert_eq!(
+		game.execute(Command::PlaceShip),
+		Err(CommandError::Fleet(ai::FleetError::NoRoom(String::from("Carrier"))))
+	);
+	assert_eq!(game.board_me(), &board);
+	assert_eq!(game.this_ship().map(|kind| kind.name.as_str()), Some("Destroyer"));
+
+	// one row higher the carrier still fits along the bottom
+	game.execute(Command::Move(Direction::Up)).unwrap();
+	assert!(matches!(game.execute(Command::PlaceShip), Ok(Outcome::ShipPlaced(_))));
+	assert_eq!((game.position(), game.rotation()), ((0, 3), Rotation::Horizontal));
+	assert!(matches!(game.execute(Command::PlaceShip), Ok(Outcome::FleetPlaced(_))));
+	assert_eq!(game.board_me().fleet().get_ships().len(), 3);
+}
+
+#[test]
+fn battle_works() {
+	let rules = Rules {
+		fleet: vec![ShipClass::new("Destroyer", 2, 1)],
+		..Rules::default()
+	};
+	let destroyer = rules.fleet[0].clone();
+	let board_secret = movement::place_ship(Board::default(), 4, 4, &destroyer, &Rotation::Horizontal);
+	let mut game = Game::with_board_secret(rules, board_secret);
+	place_fleet(&mut game);
+
+	let outcome = game.execute(Command::Fire { pos_x: 4, pos_y: 4 }).unwrap();
+	assert!(matches!(
+		outcome,
+		Outcome::Shot(Shot {
+			hit_type: HitType::Hit,
+			sunk: None,
+			..
+		})
+	));
+	assert_eq!(game.turn(), Actor::Me);
+	assert_eq!(game.execute(Command::Fire { pos_x: 4, pos_y: 4 }), Err(CommandError::AlreadyShot));
+	assert_eq!(game.execute(Command::Fire { pos_x: 10, pos_y: 4 }), Err(CommandError::OutOfBounds));
+
+	let outcome = game.execute(Command::Fire { pos_x: 0, pos_y: 0 }).unwrap();
+	assert!(matches!(
+		outcome,
+		Outcome::Shot(Shot {
+			hit_type: HitType::Miss,
+			..
+		})
+	));
+	assert_eq!(game.turn(), Actor::Ai);
+	assert_eq!(game.execute(Command::Fire { pos_x: 5, pos_y: 4 }), Err(CommandError::NotYourTurn));
+
+	while game.turn() == Actor::Ai && game.phase() == Phase::Battle {
+		let shot = game.play_ai_turn().unwrap();
+		assert_eq!(shot.actor, Actor::Ai);
+		assert_ne!(game.board_me()[shot.pos_y][shot.pos_x], Cell::Empty);
+	}
+	assert_eq!(game.play_ai_turn(), Err(CommandError::NotYourTurn));
+
+	let outcome = game.execute(Command::Fire { pos_x: 5, pos_y: 4 }).unwrap();
+	match outcome {
+		Outcome::Shot(shot) => {
+			assert_eq!(shot.hit_type, HitType::HitNSunk);
+			assert_eq!(shot.sunk.unwrap().class, destroyer);
+		}
+		_ => panic!("Expected a shot"),
+	}
+	assert_eq!(game.phase(), Phase::Finished(Actor::Me));
+	assert_eq!(game.execute(Command::Fire { pos_x: 6, pos_y: 4 }), Err(CommandError::WrongPhase));
+}
+
+#[test]
+fn sinking_marks_water() {
+	let rules = Rules {
+		fleet: vec![ShipClass::new("Destroyer", 2, 2)],
+		adjacency: Adjacency::NoEdgeOrCorner,
+		mark_water: true,
+		..Rules::default()
+	};
+	let board_secret = notation::parse(
+		"
+		AA........
+		..........
+		..........
+		..........
+		..........
+		.....BB...
+		..........
+		..........
+		..........
+		..........
+
+		A Destroyer
+		B Destroyer
+		",
+	)
+	.unwrap();
+	let mut game = Game::with_board_secret(rules, board_secret);
+	place_fleet(&mut game);
+
+	game.execute(Command::Fire { pos_x: 0, pos_y: 0 }).unwrap();
+	let shot = match game.execute(Command::Fire { pos_x: 1, pos_y: 0 }) {
+		Ok(Outcome::Shot(shot)) => shot,
+		outcome => panic!("Unexpected outcome {:?}", outcome),
+	};
+	assert_eq!(shot.hit_type, HitType::HitNSunk);
+	assert_eq!(shot.water, vec![(0, 1), (1, 1), (2, 0), (2, 1)]);
+	assert_eq!(
+		notation::format(game.board_ai()),
+		"xxo.......\nooo.......\n..........\n..........\n..........\n..........\n..........\n..........\n..........\n..........\n"
+	);
+	assert_eq!(game.turn(), Actor::Ai);
+}
+
+#[test]
+fn hints_work() {
+	let rules = Rules {
+		fleet: vec![ShipClass::new("Destroyer", 2, 1)],
+		hints: 2,
+		..Rules::default()
+	};
+	let board_secret = notation::parse(
+		"
+		..........
+		..........
+		..........
+		..........
+		....AA....
+		..........
+		..........
+		..........
+		..........
+		..........
+
+		A Destroyer
+		",
+	)
+	.unwrap();
+	let mut game = Game::with_board_secret(rules, board_secret);
+	assert_eq!(game.execute(Command::Hint), Err(CommandError::WrongPhase));
+	place_fleet(&mut game);
+
+	// a hit turns the hint towards the rest of the ship
+	game.execute(Command::Fire { pos_x: 4, pos_y: 4 }).unwrap();
+	let hint = match game.execute(Command::Hint) {
+		Ok(Outcome::Hint(hint)) => hint,
+		outcome => panic!("Unexpected outcome {:?}", outcome),
+	};
+	assert_eq!((hint.pos_x, hint.pos_y), (4, 3));
+	assert_eq!(hint.get_heat(4, 3), 1.0);
+	assert!(hint.get_heat(0, 0) < 0.1);
+	assert_eq!(game.hints_left(), 1);
+
+	game.execute(Command::Hint).unwrap();
+	assert_eq!(game.execute(Command::Hint), Err(CommandError::NoHintsLeft));
+	assert_eq!(game.hints_left(), 0);
+
+	game.execute(Command::Fire { pos_x: 5, pos_y: 4 }).unwrap();
+	assert_eq!(game.execute(Command::Hint), Err(CommandError::WrongPhase));
+}
+
+#[test]
+fn whole_game_works() {
+	let mut game = Game::new(Rules::default()).unwrap();
+	place_fleet(&mut game);
+
+	let (size_x, size_y) = (game.rules().size_x, game.rules().size_y);
+	let mut targets = (0..size_y).flat_map(|y| (0..size_x).map(move |x| (x, y)));
+	while game.phase() == Phase::Battle {
+		match game.turn() {
+			Actor::Me => {
+				let (pos_x, pos_y) = targets.next().unwrap();
+				game.execute(Command::Fire { pos_x, pos_y }).unwrap();
+			}
+			Actor::Ai => {
+				game.play_ai_turn().unwrap();
+			}
+		}
+	}
+
+	match game.phase() {
+		Phase::Finished(Actor::Me) => assert_eq!(game::get_score(game.board_ai()), format!("{:0>2}", game.fleet_size())),
+		Phase::Finished(Actor::Ai) => assert_eq!(game::get_score(game.board_me()), format!("{:0>2}", game.fleet_size())),
+		_ => panic!("The game should be finished"),
+	}
+}
+
+#[test]
+fn adaptive_games_learn_from_the_profile() {
+	let get_first_shot = |profile: Profile| {
+		let rules = Rules {
+			fleet: vec![ShipClass::new("Destroyer", 2, 1)],
+			difficulty: Difficulty::Adaptive,
+			profile,
+			seed: Some(1),
+			..Rules::default()
+		};
+		let mut game = Game::new(rules).unwrap();
+		game.execute(Command::Randomise).unwrap();
+		game.execute(Command::Confirm).unwrap();
+		game.execute(Command::Fire { pos_x: 9, pos_y: 9 }).unwrap();
+		let shot = game.play_ai_turn().unwrap();
+		(shot.pos_x, shot.pos_y)
+	};
+
+	// my destroyer always stood upright in the corner so that is where it looks first
+	let mut board = Board::default();
+	board = movement::place_ship(board, 0, 0, &ShipClass::new("Destroyer", 2, 1), &Rotation::Vertical);
+	let mut profile = Profile::default();
+	for _ in 0..5 {
+		profile.record(&board, &[]);
+	}
+	assert_eq!(get_first_shot(profile), (0, 0));
+	assert_ne!(get_first_shot(Profile::default()), (0, 0));
+}
+
+#[test]
+fn seeded_games_replay() {
+	// I fill my fleet at random and sweep the board row by row, everything else is up to the seed
+	let play = |seed: u64, explain: bool| {
+		let rules = Rules {
+			seed: Some(seed),
+			..Rules::default()
+		};
+		let mut game = Game::new(rules).unwrap();
+		assert_eq!(game.seed(), seed);
+		game.execute(Command::Randomise).unwrap();
+		game.execute(Command::Confirm).unwrap();
+
+		let mut shots = Vec::new();
+		let mut cells = (0..config::SIZE_Y).flat_map(|pos_y| (0..config::SIZE_X).map(move |pos_x| (pos_x, pos_y)));
+		while game.phase() == Phase::Battle {
+			let shot = match game.turn() {
+				Actor::Me => {
+					let (pos_x, pos_y) = cells.next().unwrap();
+					match game.execute(Command::Fire { pos_x, pos_y }).unwrap() {
+						Outcome::Shot(shot) => shot,
+						outcome => panic!("{:?}", outcome),
+					}
+				}
+				Actor::Ai => {
+					if explain {
+						assert!(game.explain_ai_turn().is_some());
+					}
+					game.play_ai_turn().unwrap()
+				}
+			};
+			shots.push((shot.actor, shot.pos_x, shot.pos_y));
+		}
+
+		(notation::format(game.board_me()), notation::format(game.board_secret()), shots)
+	};
+
+	assert_eq!(play(7, false), play(7, false));
+	assert_ne!(play(7, false), play(8, false));
+
+	// looking at what the AI weighs does not change its shots
+	assert_eq!(play(7, false), play(7, true));
+}
@@ -1,14 +1,23 @@
-use crate::config;
+//! The rules for scoring and resolving a single shot
+
+use crate::board::Board;
+use crate::movement::{self, Adjacency};
+use crate::ships::PlacedShip;
 use crate::Cell;
 
-#[derive(Debug, PartialEq)]
+#[cfg(test)]
+use crate::{notation, ships::ShipClass, Rotation};
+
+/// What a shot did
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum HitType {
 	Hit,
 	HitNSunk,
 	Miss,
 }
 
-pub fn get_score(board: &config::Board) -> String {
+/// The amount of damaged cells on a board as a two digit string
+pub fn get_score(board: &Board) -> String {
 	let mut score = 0;
 
 	for row in board {
@@ -24,20 +33,13 @@ pub fn get_score(board: &config::Board) -> String {
 
 #[test]
 fn get_score_works() {
-	let mut board = [[Cell::Empty; config::SIZE_X]; config::SIZE_Y];
+	let mut board = Board::default();
 	assert_eq!(get_score(&board), String::from("00"));
-	board[0][0] = Cell::ShipTwo([0, 0, 0, 0]);
+	board[0][0] = Cell::Ship(0);
 	assert_eq!(get_score(&board), String::from("00"));
-	board[1][0] = Cell::ShipOne([0, 0]);
-	board[1][1] = Cell::ShipOne([0, 0]);
-	board[1][2] = Cell::ShipOne([0, 0]);
-	board[1][3] = Cell::ShipOne([0, 0]);
-	board[1][4] = Cell::ShipOne([0, 0]);
-	board[1][5] = Cell::ShipOne([0, 0]);
-	board[1][6] = Cell::ShipOne([0, 0]);
-	board[1][7] = Cell::ShipOne([0, 0]);
-	board[1][8] = Cell::ShipOne([0, 0]);
-	board[1][9] = Cell::ShipOne([0, 0]);
+	for x in 0..10 {
+		board[1][x] = Cell::Ship(1);
+	}
 	assert_eq!(get_score(&board), String::from("00"));
 	board[1][0] = Cell::Damage;
 	assert_eq!(get_score(&board), String::from("01"));
@@ -61,66 +63,44 @@ fn get_score_works() {
 	assert_eq!(get_score(&board), String::from("10"));
 }
 
-pub fn get_hit_type(board_damage: &config::Board, board_ships: &config::Board, pos_x: usize, pos_y: usize) -> HitType {
-	let mut result = HitType::Miss;
+/// What a shot at these coordinates would do
+///
+/// `board_damage` holds the shots fired so far, `board_ships` holds the ships that are being shot at.
+pub fn get_hit_type(board_damage: &Board, board_ships: &Board, pos_x: usize, pos_y: usize) -> HitType {
+	let ship = match board_ships[pos_y][pos_x] {
+		Cell::Ship(id) => board_ships.fleet().get(id),
+		_ => None,
+	};
 
-	match board_ships[pos_y][pos_x] {
-		Cell::ShipOne(_) => {
-			result = HitType::HitNSunk;
-		}
-		Cell::ShipTwo(coords) => {
-			if coords[0] == pos_x && coords[1] == pos_y {
-				result = match board_damage[coords[3]][coords[2]] {
-					Cell::Damage => HitType::HitNSunk,
-					_ => HitType::Hit,
-				};
-			} else {
-				result = match board_damage[coords[1]][coords[0]] {
-					Cell::Damage => HitType::HitNSunk,
-					_ => HitType::Hit,
-				};
-			}
-		}
-		Cell::ShipThree(coords) => {
-			if coords[0] == pos_x && coords[1] == pos_y {
-				result = match (board_damage[coords[3]][coords[2]], board_damage[coords[5]][coords[4]]) {
-					(Cell::Damage, Cell::Damage) => HitType::HitNSunk,
-					(_, _) => HitType::Hit,
-				};
-			} else if coords[2] == pos_x && coords[3] == pos_y {
-				result = match (board_damage[coords[1]][coords[0]], board_damage[coords[5]][coords[4]]) {
-					(Cell::Damage, Cell::Damage) => HitType::HitNSunk,
-					(_, _) => HitType::Hit,
-				};
+	match ship {
+		Some(ship) => {
+			let is_sunk =
+				ship.cells.iter().all(|(x, y)| (*x == pos_x && *y == pos_y) || board_damage[*y][*x] == Cell::Damage);
+
+			if is_sunk {
+				HitType::HitNSunk
 			} else {
-				result = match (board_damage[coords[1]][coords[0]], board_damage[coords[3]][coords[2]]) {
-					(Cell::Damage, Cell::Damage) => HitType::HitNSunk,
-					(_, _) => HitType::Hit,
-				};
+				HitType::Hit
 			}
 		}
-		_ => {}
-	};
-
-	result
+		None => HitType::Miss,
+	}
 }
 
 #[test]
 fn hit_type_works() {
-	let mut board = [[Cell::Empty; config::SIZE_X]; config::SIZE_Y];
+	let destroyer = ShipClass::new("Destroyer", 2, 1);
+	let cruiser = ShipClass::new("Cruiser", 3, 1);
+
+	let mut board = Board::default();
 	assert_eq!(get_hit_type(&board, &board, 0, 0), HitType::Miss);
-	board[0][0] = Cell::ShipOne([0, 0]);
-	assert_eq!(get_hit_type(&board, &board, 0, 0), HitType::HitNSunk);
 
-	board[2][2] = Cell::ShipTwo([2, 2, 3, 2]);
-	board[2][3] = Cell::ShipTwo([2, 2, 3, 2]);
+	board = movement::place_ship(board, 2, 2, &destroyer, &Rotation::Horizontal);
 	assert_eq!(get_hit_type(&board, &board, 2, 2), HitType::Hit);
 	board[2][2] = Cell::Damage;
 	assert_eq!(get_hit_type(&board, &board, 3, 2), HitType::HitNSunk);
 
-	board[5][5] = Cell::ShipThree([5, 5, 5, 6, 5, 7]);
-	board[6][5] = Cell::ShipThree([5, 5, 5, 6, 5, 7]);
-	board[7][5] = Cell::ShipThree([5, 5, 5, 6, 5, 7]);
+	board = movement::place_ship(board, 5, 5, &cruiser, &Rotation::Vertical);
 	assert_eq!(get_hit_type(&board, &board, 5, 6), HitType::Hit);
 	board[6][5] = Cell::Damage;
 	assert_eq!(get_hit_type(&board, &board, 5, 7), HitType::Hit);
@@ -128,14 +108,70 @@ fn hit_type_works() {
 	assert_eq!(get_hit_type(&board, &board, 1, 1), HitType::Miss);
 	assert_eq!(get_hit_type(&board, &board, 5, 5), HitType::HitNSunk);
 
-	board = [[Cell::Empty; config::SIZE_X]; config::SIZE_Y];
-	let mut board_secret = [[Cell::Empty; config::SIZE_X]; config::SIZE_Y];
-	board_secret[5][5] = Cell::ShipThree([5, 5, 5, 6, 5, 7]);
-	board_secret[6][5] = Cell::ShipThree([5, 5, 5, 6, 5, 7]);
-	board_secret[7][5] = Cell::ShipThree([5, 5, 5, 6, 5, 7]);
+	board = Board::default();
+	let board_secret = movement::place_ship(Board::default(), 5, 5, &cruiser, &Rotation::Vertical);
 	assert_eq!(get_hit_type(&board, &board_secret, 5, 6), HitType::Hit);
 	board[6][5] = Cell::Damage;
 	assert_eq!(get_hit_type(&board, &board_secret, 5, 7), HitType::Hit);
 	board[7][5] = Cell::Damage;
 	assert_eq!(get_hit_type(&board, &board_secret, 5, 5), HitType::HitNSunk);
 }
+
+#[test]
+fn hit_type_works_for_any_length() {
+	let carrier = ShipClass::new("Carrier", 5, 1);
+	let patrol_boat = ShipClass::new("Patrol boat", 1, 1);
+
+	let mut board = movement::place_ship(Board::default(), 0, 0, &carrier, &Rotation::Horizontal);
+	board = movement::place_ship(board, 9, 9, &patrol_boat, &Rotation::Horizontal);
+	assert_eq!(get_hit_type(&board, &board, 9, 9), HitType::HitNSunk);
+
+	for x in 0..4 {
+		assert_eq!(get_hit_type(&board, &board, x, 0), HitType::Hit);
+		board[0][x] = Cell::Damage;
+	}
+	assert_eq!(get_hit_type(&board, &board, 4, 0), HitType::HitNSunk);
+}
+
+/// The ship that was sunk by the last shot at these coordinates
+pub fn get_sunk_ship(board_ships: &Board, pos_x: usize, pos_y: usize) -> Option<&PlacedShip> {
+	board_ships.fleet().get_at(pos_x, pos_y).filter(|ship| ship.is_sunk())
+}
+
+#[test]
+fn get_sunk_ship_works() {
+	let destroyer = ShipClass::new("Destroyer", 2, 1);
+	let mut board = movement::place_ship(Board::default(), 2, 2, &destroyer, &Rotation::Horizontal);
+
+	board.fleet_mut().record_hit(2, 2);
+	assert!(get_sunk_ship(&board, 2, 2).is_none());
+	board.fleet_mut().record_hit(3, 2);
+	assert_eq!(get_sunk_ship(&board, 3, 2).unwrap().class.name, "Destroyer");
+	assert!(get_sunk_ship(&board, 4, 2).is_none());
+}
+
+/// Mark the cells around a sunk ship that the adjacency rule keeps free as water on the attackers board and return
+/// the cells that had not been shot at yet
+pub fn mark_water(board_shots: &mut Board, ship: &PlacedShip, adjacency: Adjacency) -> Vec<(usize, usize)> {
+	let water: Vec<(usize, usize)> = movement::get_surroundings(board_shots, &ship.cells, adjacency)
+		.into_iter()
+		.filter(|(pos_x, pos_y)| board_shots[*pos_y][*pos_x] != Cell::Shot && board_shots[*pos_y][*pos_x] != Cell::Damage)
+		.collect();
+
+	for (pos_x, pos_y) in &water {
+		board_shots[*pos_y][*pos_x] = Cell::Shot;
+	}
+
+	water
+}
+
+#[test]
+fn mark_water_works() {
+	let mut board = notation::parse("Ao..\nA...\n....\n\nA Destroyer\n").unwrap();
+	let ship = board.fleet().get_ships()[0].clone();
+
+	assert_eq!(mark_water(&mut board.clone(), &ship, Adjacency::Any), vec![]);
+	assert_eq!(mark_water(&mut board.clone(), &ship, Adjacency::NoEdge), vec![(1, 1), (0, 2)]);
+	assert_eq!(mark_water(&mut board, &ship, Adjacency::NoEdgeOrCorner), vec![(1, 1), (0, 2), (1, 2)]);
+	assert_eq!(notation::format(&board), "Ao..\nAo..\noo..\n\nA Destroyer\n");
+}
@@ -1,23 +1,61 @@
 extern crate termion;
 
 use std::io;
-
-use crate::config;
-use crate::game;
-use crate::Cell;
+use std::path::Path;
+
+use battleship::ai::Insight;
+use battleship::board::Board;
+use battleship::config;
+use battleship::engine::Hint;
+use battleship::game;
+use battleship::history::History;
+use battleship::movement::{Edge, PlacementError};
+use battleship::preset::PresetError;
+use battleship::Cell;
 
 use termion::color;
-use Cell::{Crosshair, Damage, Empty, Placeholder, Ship, ShipOne, ShipThree, ShipTwo, Shot};
+use Cell::{Crosshair, Damage, Empty, Placeholder, Ship, Shot};
 
+#[derive(Copy, Clone, PartialEq)]
 pub enum Round {
 	One,
+	// choosing a placed ship to pick up again with a crosshair on my own board
+	Pick,
+	// typing the name to save our placed fleet under
+	Name,
 	Two,
 }
 
-pub fn get_padding() -> String {
+/// What is drawn on top of the board I shoot at
+#[derive(Copy, Clone)]
+pub enum Overlay<'a> {
+	None,
+	// the cell a hint points at
+	Hint(&'a Hint),
+	// the cell a hint points at and how likely every unknown cell is to hold a ship
+	Heatmap(&'a Hint),
+}
+
+impl Overlay<'_> {
+	fn is_hint(&self, pos_x: usize, pos_y: usize) -> bool {
+		match self {
+			Overlay::Hint(hint) | Overlay::Heatmap(hint) => hint.pos_x == pos_x && hint.pos_y == pos_y,
+			Overlay::None => false,
+		}
+	}
+
+	fn get_heat(&self, pos_x: usize, pos_y: usize) -> Option<f64> {
+		match self {
+			Overlay::Heatmap(hint) => Some(hint.get_heat(pos_x, pos_y)),
+			_ => None,
+		}
+	}
+}
+
+pub fn get_padding(size_x: usize) -> String {
 	let mut padding: f32 = 0.0;
 	let size = termion::terminal_size();
-	let board_size = (config::SIZE_X as f32 * 3.0 * 2.0) + 11.0;
+	let board_size = (size_x as f32 * 3.0 * 2.0) + 11.0;
 
 	if let Ok((width, _)) = size {
 		padding = ((width as f32 - board_size) / 2.0).floor();
@@ -26,10 +64,10 @@ pub fn get_padding() -> String {
 	format!("{:width$}", "", width = padding as usize)
 }
 
-pub fn get_header() -> String {
-	let padding = get_padding();
+pub fn get_header(size_x: usize) -> String {
+	let padding = get_padding(size_x);
 	let reset = color::Fg(color::White);
-	let board_size = (config::SIZE_X * 3 * 2) + 11;
+	let board_size = (size_x * 3 * 2) + 11;
 	let inner_size = ((board_size - 46) / 2) as f32;
 	let inner_padding = format!("{:width$}", "", width = inner_size.floor() as usize);
 
@@ -80,23 +118,23 @@ pub fn get_header() -> String {
 	format!("{}{}{}{}{}{}\r\n\r\n", logo1, logo2, logo3, logo4, logo5, logo6)
 }
 
-pub fn get_score(board_me: config::Board, board_ai: config::Board, round: Round) -> String {
-	let padding = get_padding();
+pub fn get_score(board_me: &Board, board_ai: &Board, round: Round) -> String {
+	let padding = get_padding(board_me.size_x());
 
 	let score_me = match round {
-		Round::One => String::from("--"),
-		Round::Two => game::get_score(&board_ai),
+		Round::One | Round::Pick | Round::Name => String::from("--"),
+		Round::Two => game::get_score(board_ai),
 	};
 
 	let score_ai = match round {
-		Round::One => String::from("--"),
-		Round::Two => game::get_score(&board_me),
+		Round::One | Round::Pick | Round::Name => String::from("--"),
+		Round::Two => game::get_score(board_me),
 	};
 
 	let user1 = "ME";
 	let user2 = "AI";
 
-	let inner_size = (config::SIZE_X * 3) + 5 - 15 - 7 - 2 - 3;
+	let inner_size = (board_me.size_x() * 3) + 5 - 15 - 7 - 2 - 3;
 	let inner_padding = &format!("{:width$}", "", width = inner_size);
 
 	format!(
@@ -114,12 +152,13 @@ pub fn get_score(board_me: config::Board, board_ai: config::Board, round: Round)
 
 // return one line of a board and interpret states to visual styles
 fn get_board_row(
-	board_row: &config::BoardRow,
+	board_row: &[Cell],
 	y: usize,
 	pos_x: usize,
 	pos_y: usize,
 	cell: Cell,
 	show_position: bool,
+	overlay: Overlay,
 ) -> String {
 	let mut output = String::new();
 
@@ -141,11 +180,56 @@ fn get_board_row(
 				output += &format!("{}{}{}", color::Fg(color::Green), config::SHIP, color::Fg(color::White))
 			}
 			(Shot, _, _) => output += config::SHOT,
-			(Ship, _, _) | (ShipOne(_), _, _) | (ShipTwo(_), _, _) | (ShipThree(_), _, _) => output += config::SHIP,
+			(Ship(_), _, _) => output += config::SHIP,
 			(Damage, _, _) => output += config::DAMAGE,
-			(_, _, _) => match x % 2 {
-				0 => output += &format!("{}{}{}", color::Fg(color::LightWhite), config::EMPTY, color::Fg(color::White)),
-				_ => output += &format!("{}{}{}", color::Fg(color::LightBlack), config::EMPTY, color::Fg(color::White)),
+			(_, this_pos_x, this_pos_y) if overlay.is_hint(this_pos_x, this_pos_y) => {
+				output += &format!("{}{}{}", color::Fg(color::Yellow), config::HINT, color::Fg(color::White))
+			}
+			(_, _, _) => match (overlay.get_heat(x, y), x % 2) {
+				(Some(heat), _) => {
+					output += &format!("{}{}{}", color::Fg(get_heat_color(heat)), config::EMPTY, color::Fg(color::White))
+				}
+				(None, 0) => output += &format!("{}{}{}", color::Fg(color::LightWhite), config::EMPTY, color::Fg(color::White)),
+				(None, _) => output += &format!("{}{}{}", color::Fg(color::LightBlack), config::EMPTY, color::Fg(color::White)),
+			},
+		}
+	}
+
+	output
+}
+
+// from dark grey for cells that are unlikely to hold a ship to bright red for the likeliest
+fn get_heat_color(heat: f64) -> color::AnsiValue {
+	color::AnsiValue::rgb(1 + (heat.clamp(0.0, 1.0) * 4.0).round() as u8, 1, 1)
+}
+
+// return one line of what the AI weighs for its next shot at my board, scores are shown relative to the best cell and
+// queued cells by the group they are in
+fn get_insight_row(insight: &Insight, board_row: &[Cell], y: usize) -> String {
+	let mut output = String::new();
+	let max = match insight {
+		Insight::Scores(scores) => scores.iter().flatten().copied().fold(0.0, f64::max),
+		Insight::Queue(_) => 0.0,
+	};
+
+	for (x, item) in board_row.iter().enumerate() {
+		match (item, insight) {
+			(Shot, _) => output += config::SHOT,
+			(Damage, _) => output += config::DAMAGE,
+			(_, Insight::Scores(scores)) => match scores[y][x] {
+				score if score <= 0.0 => output += &format!("{} · {}", color::Fg(color::LightBlack), color::Fg(color::White)),
+				score if score >= max => output += &format!("{} 99{}", color::Fg(color::Yellow), color::Fg(color::White)),
+				score => {
+					let heat = score / max;
+					output +=
+						&format!(" {}{:>2}{}", color::Fg(get_heat_color(heat)), (heat * 99.0).round(), color::Fg(color::White))
+				}
+			},
+			(_, Insight::Queue(queue)) => match queue.iter().position(|cells| cells.contains(&(x, y))) {
+				Some(0) => output += &format!("{} 1 {}", color::Fg(color::Yellow), color::Fg(color::White)),
+				Some(group) if group < 9 => output += &format!(" {} ", group + 1),
+				Some(_) => output += " + ",
+				None => output += &format!("{} · {}", color::Fg(color::LightBlack), color::Fg(color::White)),
 			},
 		}
 	}
@@ -154,43 +238,73 @@ fn get_board_row(
 }
 
 pub fn get_board(
-	board_me: &config::Board,
-	board_ai: &config::Board,
+	board_me: &Board,
+	board_ai: &Board,
 	pos_x: usize,
 	pos_y: usize,
 	round: Round,
+	overlay: Overlay,
+	insight: Option<&Insight>,
 ) -> String {
-	let padding = get_padding();
+	let padding = get_padding(board_me.size_x());
 
 	let mut coords = String::from(" ");
-	for i in 1..=config::SIZE_X {
+	for i in 1..=board_me.size_x() {
 		coords += &format!(" {:^2}", i);
 	}
 	let coord_top = &format!(" {coords}   ║   {coords}", coords = coords);
 
-	let frame_top = &format!("┌{:─^width$}┐", "", width = config::SIZE_X * 3);
+	let frame_top = &format!("┌{:─^width$}┐", "", width = board_me.size_x() * 3);
 	let top = &format!(" {frame_top}  ║   {frame_top}", frame_top = frame_top);
 
-	let frame_bottom = &format!("└{:─^width$}┘", "", width = config::SIZE_X * 3);
+	let frame_bottom = &format!("└{:─^width$}┘", "", width = board_me.size_x() * 3);
 	let bottom = &format!(" {frame_bottom}  ║   {frame_bottom}", frame_bottom = frame_bottom);
-	let show_position = match round {
-		Round::One => false,
-		Round::Two => true,
+	let (show_position_me, show_position_ai) = match round {
+		Round::One | Round::Name => (false, false),
+		Round::Pick => (true, false),
+		Round::Two => (false, true),
 	};
 
-	let mut output = format!("{}{}{}\r\n{}{}\r\n", padding, color::Fg(color::White), coord_top, padding, top);
-	for row in 0..config::SIZE_Y {
+	// what the AI weighs sits in the padding left of my board so both boards stay where they are
+	let panel_width = board_me.size_x() * 3 + 5;
+	let (padding, panel) = match insight {
+		Some(insight) => {
+			let padding = format!("{:width$}", "", width = padding.len().saturating_sub(panel_width));
+			let mut panel = vec![
+				format!(
+					"{}{:<width$}{}",
+					color::Fg(color::Magenta),
+					" AI NEXT SHOT",
+					color::Fg(color::White),
+					width = panel_width
+				),
+				format!("{}   ", frame_top),
+			];
+			for row in 0..board_me.size_y() {
+				panel.push(format!("│{}│   ", get_insight_row(insight, &board_me[row], row)));
+			}
+			panel.push(format!("{}   ", frame_bottom));
+			(padding, panel)
+		}
+		None => (padding, vec![String::new(); board_me.size_y() + 3]),
+	};
+
+	let mut output =
+		format!("{}{}{}{}\r\n{}{}{}\r\n", padding, panel[0], color::Fg(color::White), coord_top, padding, panel[1], top);
+	for row in 0..board_me.size_y() {
 		output += &padding;
+		output += &panel[row + 2];
 		output += &make_alphabetic(row);
 		output += "│";
-		output += &get_board_row(&board_me[row], row, pos_x, pos_y, Empty, false);
+		output += &get_board_row(&board_me[row], row, pos_x, pos_y, Crosshair, show_position_me, Overlay::None);
 		output += "│  ║  ";
 		output += &make_alphabetic(row);
 		output += "│";
-		output += &get_board_row(&board_ai[row], row, pos_x, pos_y, Crosshair, show_position);
+		output += &get_board_row(&board_ai[row], row, pos_x, pos_y, Crosshair, show_position_ai, overlay);
 		output += "│\r\n";
 	}
 	output += &padding;
+	output += &panel[board_me.size_y() + 2];
 	output += bottom;
 	output += "\r\n\r\n";
 	output += &format!("{}", color::Fg(color::White));
@@ -199,16 +313,7 @@ pub fn get_board(
 }
 
 fn make_alphabetic(y: usize) -> String {
-	let alphabet = ('A'..='Z')
-		.filter_map(|c| {
-			let c = c as char;
-			if c.is_alphabetic() {
-				Some(c)
-			} else {
-				None
-			}
-		})
-		.collect::<Vec<_>>();
+	let alphabet = ('A'..='Z').filter(|c| c.is_alphabetic()).collect::<Vec<_>>();
 
 	String::from(alphabet[y])
 }
@@ -227,34 +332,235 @@ fn get_coord_works() {
 	assert_eq!(get_coord(9, 9), String::from("J10"));
 }
 
-pub fn get_round1_instructions() -> String {
-	let padding = get_padding().len();
-	let board_size = config::SIZE_X * 3 * 2;
-	let all_size = (((padding * 2) + board_size + 11 - 71) / 2) as f32;
+#[cfg(test)]
+use battleship::engine::{Command, Game, Outcome, Rules};
+
+#[test]
+fn get_board_row_shows_hints() {
+	let mut game = Game::new(Rules {
+		seed: Some(1),
+		..Rules::default()
+	})
+	.unwrap();
+	game.execute(Command::Randomise).unwrap();
+	game.execute(Command::Confirm).unwrap();
+	let hint = match game.execute(Command::Hint) {
+		Ok(Outcome::Hint(hint)) => hint,
+		_ => panic!("Expected a hint"),
+	};
+	let get_row = |overlay| get_board_row(&game.board_ai()[hint.pos_y], hint.pos_y, 0, 0, Crosshair, false, overlay);
+
+	assert!(!get_row(Overlay::None).contains(config::HINT));
+	assert_eq!(get_row(Overlay::Hint(&hint)).matches(config::HINT).count(), 1);
+	assert!(get_row(Overlay::Hint(&hint)).contains(&color::Fg(color::LightBlack).to_string()));
+	assert!(!get_row(Overlay::Heatmap(&hint)).contains(&color::Fg(color::LightBlack).to_string()));
+}
+
+#[test]
+fn get_insight_row_works() {
+	let row = [Shot, Empty, Empty, Ship(0)];
+
+	let queue = Insight::Queue(vec![vec![(1, 0)], vec![(1, 0), (2, 0)]]);
+	let output = get_insight_row(&queue, &row, 0);
+	assert!(output.starts_with(config::SHOT));
+	assert!(output.contains(" 1 ") && output.contains(" 2 ") && output.contains(" · "));
+
+	let scores = Insight::Scores(vec![vec![0.0, 0.2, 0.1, 0.0]]);
+	let output = get_insight_row(&scores, &row, 0);
+	assert!(output.contains(" 99") && output.contains("50") && output.contains(" · "));
+
+	let board = Board::new(10, 1);
+	let scores = Insight::Scores(vec![vec![1.0; 10]]);
+	assert!(get_board(&board, &board, 0, 0, Round::Two, Overlay::None, Some(&scores)).contains("AI NEXT SHOT"));
+	assert!(!get_board(&board, &board, 0, 0, Round::Two, Overlay::None, None).contains("AI NEXT SHOT"));
+}
+
+pub fn get_history(history: &History, size_x: usize) -> String {
+	let padding = get_padding(size_x);
+	let latest = history.get_history_slice();
+	let board_size = (size_x * 3 * 2) + 11;
+	let inner_size = (((board_size * 2) - 5) / 2) as f32;
+	let size = inner_size.floor() as usize;
+
+	let mut output = format!("{} HISTORY\r\n", padding);
+	output += &format!("{} ┌{:─^width$}┐\r\n", padding, "", width = size);
+	output += &format!("{} │ {:<width$} │\r\n", padding, latest[0], width = (size + 16));
+	let msg2 = if latest.len() > 1 {
+		latest[1].clone()
+	} else {
+		format!("{}{}", color::Fg(color::White), color::Fg(color::White))
+	};
+	output += &format!("{} │ {:<width$} │\r\n", padding, msg2, width = (size + 16));
+	let msg3 = if latest.len() > 2 {
+		latest[2].clone()
+	} else {
+		format!("{}{}", color::Fg(color::White), color::Fg(color::White))
+	};
+	output += &format!("{} │ {:<width$} │\r\n", padding, msg3, width = (size + 16));
+	output += &format!("{} └{:─^width$}┘\r\n", padding, "", width = size);
+
+	output
+}
+
+pub fn get_round1_instructions(size_x: usize, round: Round, is_fleet_placed: bool, notice: &str) -> String {
+	let padding = get_padding(size_x).len();
+	let board_size = size_x * 3 * 2;
+	let all_size = (((padding * 2) + board_size + 11).saturating_sub(71) / 2) as f32;
 	let size = all_size.floor() as usize;
 	let all_padding = &format!("{:width$}", "", width = size);
 
+	let keys = match (round, is_fleet_placed) {
+		(Round::Pick, _) => format!("{}       [←↑↓→] choose a ship ║ [enter] pick it up ║ [p] cancel\r\n", all_padding),
+		(Round::Name, _) => format!("{}       [a-z] type a name ║ [enter] save layout ║ [esc] cancel\r\n", all_padding),
+		(_, true) => format!(
+			"{all_padding}     [enter] start battle ║ [f] reroll ║ [del] restart ║ [q] quit\r\n{all_padding}     [p] pick up ║ [u] undo ║ [s] save layout ║ [l] load layout",
+			all_padding = all_padding,
+		),
+		(_, false) => format!(
+			"{all_padding} [←↑↓→] position ║ [r] rotate ║ [enter] place ║ [del] restart ║ [q] quit\r\n{all_padding}  [f] fill at random ║ [p] pick up ║ [u] undo ║ [l] load layout",
+			all_padding = all_padding,
+		),
+	};
+
 	format!(
-		"\r\n{all_padding}      {}PLACING ROUND - Place your ships strategically on your map{}\r\n\r\n{all_padding} [←↑↓→] position ║ [r] rotate ║ [enter] place ║ [del] restart ║ [q] quit\r\n\r\n",
+		"\r\n{all_padding}      {}PLACING ROUND - Place your ships strategically on your map{}\r\n\r\n{}\r\n{all_padding} {}{}{}\r\n",
 		color::Fg(color::Green),
 		color::Fg(color::White),
+		keys,
+		color::Fg(color::Red),
+		notice,
+		color::Fg(color::White),
 		all_padding=all_padding,
 	)
 }
 
-pub fn get_round2_instructions() -> String {
-	let padding = get_padding();
+fn get_edge_name(edge: &Edge) -> &'static str {
+	match edge {
+		Edge::Left => "left",
+		Edge::Right => "right",
+		Edge::Top => "top",
+		Edge::Bottom => "bottom",
+	}
+}
+
+// explain to the player why the ship can't go where they wanted it
+pub fn get_placement_error(error: &PlacementError, board: &Board) -> String {
+	match error {
+		PlacementError::OutOfBounds(edge) => {
+			format!("The ship would stick out over the {} edge of the board", get_edge_name(edge))
+		}
+		PlacementError::Overlap(id) => match board.fleet().get(*id) {
+			Some(ship) => {
+				format!("The ship would overlap your {} at {}", ship.class.name, get_coord(ship.cells[0].0, ship.cells[0].1))
+			}
+			None => String::from("The ship would overlap another ship"),
+		},
+		PlacementError::Occupied { pos_x, pos_y } => format!("The cell {} is already taken", get_coord(*pos_x, *pos_y)),
+		PlacementError::Adjacent(id) => match board.fleet().get(*id) {
+			Some(ship) => {
+				format!(
+					"Ships may not touch but this one would touch your {} at {}",
+					ship.class.name,
+					get_coord(ship.cells[0].0, ship.cells[0].1)
+				)
+			}
+			None => String::from("Ships may not touch but this one would touch another ship"),
+		},
+	}
+}
+
+#[test]
+fn get_placement_error_works() {
+	let board = battleship::movement::place_ship(
+		Board::default(),
+		2,
+		1,
+		&battleship::ships::ShipClass::new("Cruiser", 3, 1),
+		&battleship::Rotation::Vertical,
+	);
+	assert_eq!(
+		get_placement_error(&PlacementError::OutOfBounds(Edge::Right), &board),
+		"The ship would stick out over the right edge of the board"
+	);
+	assert_eq!(get_placement_error(&PlacementError::Overlap(0), &board), "The ship would overlap your Cruiser at B3");
+	assert_eq!(get_placement_error(&PlacementError::Overlap(7), &board), "The ship would overlap another ship");
+	assert_eq!(
+		get_placement_error(&PlacementError::Adjacent(0), &board),
+		"Ships may not touch but this one would touch your Cruiser at B3"
+	);
+}
+
+// explain to the player why a saved layout can't be used in this game
+pub fn get_preset_error(error: &PresetError) -> String {
+	match error {
+		PresetError::Syntax { line } => format!("Line {} of the layouts file can't be read", line),
+		PresetError::WrongSize { size_x, size_y } => format!("It was saved for a board of {}x{}", size_x, size_y),
+		PresetError::UnknownShip(name) => format!("It has a {} too many or that is not part of the fleet", name),
+		PresetError::MissingShips => String::from("It is missing ships of the fleet"),
+		PresetError::InvalidPlacement(name, PlacementError::OutOfBounds(edge)) => {
+			format!("Its {} sticks out over the {} edge of the board", name, get_edge_name(edge))
+		}
+		PresetError::InvalidPlacement(name, PlacementError::Adjacent(_)) => {
+			format!("Its {} touches another ship but ships may not touch", name)
+		}
+		PresetError::InvalidPlacement(name, _) => format!("Its {} overlaps another ship", name),
+		PresetError::File(message) => format!("The layouts file can't be used: {}", message),
+	}
+}
+
+#[test]
+fn get_preset_error_works() {
+	assert_eq!(get_preset_error(&PresetError::WrongSize { size_x: 12, size_y: 8 }), "It was saved for a board of 12x8");
+	assert_eq!(
+		get_preset_error(&PresetError::InvalidPlacement(
+			String::from("Cruiser"),
+			PlacementError::OutOfBounds(Edge::Bottom)
+		)),
+		"Its Cruiser sticks out over the bottom edge of the board"
+	);
+	assert_eq!(
+		get_preset_error(&PresetError::InvalidPlacement(String::from("Cruiser"), PlacementError::Overlap(1))),
+		"Its Cruiser overlaps another ship"
+	);
+}
+
+pub fn get_profile_error(error: &PresetError, path: &Path) -> String {
+	match error {
+		PresetError::File(message) => format!("The profile {} can't be used: {}", path.display(), message),
+		PresetError::Syntax { line } => {
+			format!("Line {} of the profile {} can't be read, start over with --reset-profile", line, path.display())
+		}
+		_ => format!("The profile {} can't be used", path.display()),
+	}
+}
+
+pub fn get_round2_instructions(size_x: usize, fleet_size: &str, hints_left: usize) -> String {
+	let padding = get_padding(size_x);
 	format!(
-		"\r\n{}{}   PLAY - Hit all your opponents ships and reach a score of 10 to win{}\r\n\r\n{}              [←↑↓→] position ║ [enter] shoot ║ [q] quit\r\n\r\n",
+		"\r\n{}{}   PLAY - Hit all your opponents ships and reach a score of {} to win{}\r\n\r\n{}              [←↑↓→] position ║ [enter] shoot ║ [q] quit\r\n{}              [h] hint ({} left) ║ [m] heatmap\r\n\r\n",
 		padding,
 		color::Fg(color::Green),
+		fleet_size,
+		color::Fg(color::White),
+		padding,
+		padding,
+		hints_left,
+	)
+}
+
+pub fn get_step_instructions(size_x: usize) -> String {
+	let padding = get_padding(size_x);
+	format!(
+		"\r\n{}{}   AI TURN - Look at what the AI weighs before it takes its next shot{}\r\n\r\n{}              [space] next shot ║ [q] quit\r\n\r\n",
+		padding,
+		color::Fg(color::LightRed),
 		color::Fg(color::White),
 		padding,
 	)
 }
 
-pub fn get_good_bye_msg(winner: bool) -> String {
-	let padding = get_padding();
+pub fn get_good_bye_msg(winner: bool, size_x: usize, seed: u64) -> String {
+	let padding = get_padding(size_x);
 
 	let mut result = String::new();
 	if winner {
@@ -275,11 +581,19 @@ pub fn get_good_bye_msg(winner: bool) -> String {
 		result += &format!("{}", color::Fg(color::White));
 		result += &format!("{}Try again soon.\r\n", padding);
 	}
+	result += &format!("{}Replay this game with --seed {}\r\n", padding, seed);
 
 	result
 }
 
-pub fn draw(stdout: &mut dyn io::Write, score: String, board: String, history: String, instructions: String) {
+pub fn draw(
+	stdout: &mut dyn io::Write,
+	size_x: usize,
+	score: String,
+	board: String,
+	history: String,
+	instructions: String,
+) {
 	write!(
 		stdout,
 		"{}{}{}{}{}{}{}{}{}{}",
@@ -287,7 +601,7 @@ pub fn draw(stdout: &mut dyn io::Write, score: String, board: String, history: S
 		termion::cursor::Goto(1, 2),
 		termion::color::Fg(termion::color::White),
 		termion::cursor::Hide,
-		get_header(),
+		get_header(size_x),
 		score,
 		board,
 		history,
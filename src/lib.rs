@@ -0,0 +1,82 @@
+//! The engine behind the battleship game for your ANSI shell.
+//!
+//! The terminal game is a thin front-end on top of this crate. Everything it uses is available here so other
+//! front-ends, bots and analysis tools can drive whole games without a terminal:
+//!
+//! - [`board`] the heap-backed [`board::Board`] and its fleet registry
+//! - [`ships`] fleet definitions via [`ships::ShipClass`] and placed ships via [`ships::PlacedShip`]
+//! - [`movement`] placing and moving ships and crosshairs on a board
+//! - [`game`] the rules for scoring and resolving a single shot
+//! - [`engine`] the [`engine::Game`] state machine driven by [`engine::Command`]s
+//! - [`ai`] the computer opponent and the [`ai::Strategy`] it plays with
+//! - [`placement`] the personalities the AI arranges its fleet with
+//! - [`bot`] strategies of bots in any language running as a child process over a line-based protocol
+//! - [`density`] where the ships still afloat are most likely to be
+//! - [`solver`] exact hit probabilities from every arrangement of the fleet that is left
+//! - [`history`] the log of everything that happened in a game
+//! - [`preset`] named fleet layouts saved to a local file
+//! - [`profile`] my past games the adaptive AI learns my habits from
+//! - [`notation`] a plain-text format to print and parse boards
+//! - [`simulate`] many AI-vs-AI games without a terminal and the statistics of how they went
+//!
+//! ```
+//! use battleship::engine::{Command, Game, Phase, Rules};
+//!
+//! let mut game = Game::new(Rules::default()).unwrap();
+//! while game.this_ship().is_some() {
+//!     game.execute(Command::PlaceShip).unwrap();
+//! }
+//! game.execute(Command::Confirm).unwrap();
+//! assert_eq!(game.phase(), Phase::Battle);
+//! ```
+
+extern crate rand;
+extern crate termion;
+
+pub mod ai;
+pub mod board;
+pub mod bot;
+pub mod config;
+pub mod density;
+pub mod engine;
+pub mod game;
+pub mod history;
+pub mod movement;
+pub mod notation;
+pub mod placement;
+pub mod preset;
+pub mod profile;
+pub mod ships;
+pub mod simulate;
+pub mod solver;
+
+/// The random number generator behind every random decision of a game, the same seed replays the same game
+pub type GameRng = rand::rngs::StdRng;
+
+/// The state of a single cell on a board
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Cell {
+	Empty,
+	Shot,
+	/// A cell taken up by the ship with this id in the boards fleet registry
+	Ship(ships::ShipId),
+	Damage,
+	Placeholder,
+	Crosshair,
+}
+
+/// The orientation of a ship
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Rotation {
+	Horizontal,
+	Vertical,
+}
+
+/// A direction to move a ship or crosshair into
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Direction {
+	Left,
+	Right,
+	Up,
+	Down,
+}
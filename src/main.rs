@@ -1,56 +1,155 @@
-extern crate rand;
+extern crate battleship;
 extern crate termion;
 
 use std::{thread, time};
 
-mod ai;
-pub mod config;
-pub mod game;
+mod cli;
 mod gui;
-mod history;
-pub mod movement;
-pub mod ships;
 
-use history::History;
-use ships::Ship;
-use ships::ShipTracker;
+use battleship::ai::Difficulty;
+use battleship::bot::Bot;
+use battleship::engine::{self, Command, Game, Phase};
+use battleship::history::{self, History};
+use battleship::movement::{self, Adjacency};
+use battleship::preset::{self, Preset};
+use battleship::profile;
+use battleship::simulate::Player;
+use battleship::{config, game, Direction, Rotation};
 use std::io::{stdin, stdout, Write};
 use termion::event::Key;
 use termion::input::TermRead;
 use termion::raw::IntoRawMode;
 
-use Cell::{Damage, Empty, Placeholder, Shot};
-
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum Cell {
-	Empty,
-	Shot,
-	Ship,
-	ShipTwo([usize; 4]),
-	ShipThree([usize; 6]),
-	ShipFour([usize; 8]),
-	Damage,
-	Placeholder,
-	Crosshair,
+fn quit(stdout: &mut termion::raw::RawTerminal<std::io::Stdout>, seed: u64) {
+	write!(stdout, "{}{}", termion::cursor::Restore, termion::cursor::Show).unwrap();
+	stdout.flush().unwrap();
+	termion::raw::RawTerminal::suspend_raw_mode(stdout).unwrap();
+	println!("Replay this game with --seed {}", seed);
+	std::process::exit(0);
 }
 
-#[derive(Copy, Clone, Debug)]
-pub enum Rotation {
-	Horizontal,
-	Vertical,
+fn log_shot(history: &mut History, shot: &engine::Shot) {
+	let coord = gui::get_coord(shot.pos_x, shot.pos_y);
+	let entry = match (&shot.hit_type, &shot.sunk) {
+		(game::HitType::HitNSunk, Some(ship)) => format!("Shoot at {} and hit and sunk a {}", coord, ship.class.name),
+		(game::HitType::HitNSunk, None) => format!("Shoot at {} and hit and sunk a ship", coord),
+		(game::HitType::Hit, _) => format!("Shoot at {} and hit a ship", coord),
+		(game::HitType::Miss, _) => format!("Shoot at {} and missed", coord),
+	};
+
+	history.set_history(&entry, shot.actor);
+
+	if let (Some(ship), false) = (&shot.sunk, shot.water.is_empty()) {
+		let entry = format!("Marked {} cells of water around the {}", shot.water.len(), ship.class.name);
+		history.set_history(&entry, shot.actor);
+	}
+}
+
+fn load_preset(game: &mut Game, preset: &Preset) -> Result<(), String> {
+	match game.load_preset(preset) {
+		Ok(_) => Ok(()),
+		Err(engine::CommandError::Preset(error)) => {
+			Err(format!("The layout \"{}\" can't be used. {}", preset.name, gui::get_preset_error(&error)))
+		}
+		Err(_) => Err(format!("The layout \"{}\" can only be loaded while placing ships", preset.name)),
+	}
 }
 
-#[derive(Copy, Clone, Debug)]
-pub enum Direction {
-	Left,
-	Right,
-	Up,
-	Down,
+// play the games of a simulation and print how they went, returns the message to exit with when it can't run
+fn simulate(
+	rules: engine::Rules,
+	bot: Option<String>,
+	simulation: &cli::Simulation,
+	presets_path: &std::path::Path,
+) -> Result<(), String> {
+	let seed = rules.seed.unwrap_or_else(rand::random);
+	let player = match bot {
+		Some(command) => Player::Bot(command),
+		None => Player::Ai(rules.difficulty),
+	};
+
+	let report = match &simulation.versus {
+		Some(versus) => battleship::simulate::run_duel(&rules, &player, versus, simulation.games, seed)
+			.map_err(|error| cli::get_fleet_error(&error))?,
+		None => {
+			let presets = preset::load(presets_path).map_err(|error| gui::get_preset_error(&error))?;
+			let layouts: Vec<_> = presets.iter().filter_map(|preset| preset.to_board(&rules).ok()).collect();
+			if layouts.is_empty() {
+				return Err(format!("There are no saved layouts in {} that fit these rules", presets_path.display()));
+			}
+
+			battleship::simulate::run_layouts(&rules, &player, &layouts, simulation.games, seed)
+		}
+	};
+
+	if simulation.json {
+		print!("{}", battleship::simulate::format_json(&report));
+	} else {
+		println!("{} games from --seed {}\n", report.records.len(), seed);
+		print!("{}", battleship::simulate::format_table(&report));
+	}
+
+	Ok(())
 }
 
 fn main() {
-	let min_width = config::SIZE_X as u16 * 3 * 2 + 11;
-	let min_height = 7 + 2 + 1 + config::SIZE_Y as u16 + 1 + 7 + 4 + 3;
+	let mut options = match cli::parse_args(std::env::args().skip(1)) {
+		Ok(options) => options,
+		Err(message) => {
+			eprintln!("{}", message);
+			std::process::exit(1);
+		}
+	};
+	if options.help {
+		println!("{}", cli::get_help());
+		return;
+	}
+
+	let size_x = options.rules.size_x;
+	let debug = options.debug;
+	let presets_path = cli::get_presets_path();
+	let profile_path = cli::get_profile_path();
+
+	if options.reset_profile {
+		match profile::reset(&profile_path) {
+			Ok(()) => println!("Forgot the past games in {}", profile_path.display()),
+			Err(error) => {
+				eprintln!("{}", gui::get_profile_error(&error, &profile_path));
+				std::process::exit(1);
+			}
+		}
+		return;
+	}
+
+	// the adaptive AI learns from my past games on either side of a simulation too
+	let versus = options.simulation.as_ref().and_then(|simulation| simulation.versus.as_ref());
+	let is_adaptive = options.bot.is_none() && options.rules.difficulty == Difficulty::Adaptive;
+	if is_adaptive || versus == Some(&Player::Ai(Difficulty::Adaptive)) {
+		match profile::load(&profile_path) {
+			Ok(profile) => options.rules.profile = profile,
+			Err(error) => {
+				eprintln!("{}", gui::get_profile_error(&error, &profile_path));
+				std::process::exit(1);
+			}
+		}
+	}
+
+	if let Some(simulation) = &options.simulation {
+		if let Err(message) = simulate(options.rules, options.bot, simulation, &presets_path) {
+			eprintln!("{}", message);
+			std::process::exit(1);
+		}
+		return;
+	}
+
+	// the debug panel takes the width of a board on both sides so the boards can stay in the middle
+	let panel_width = if debug {
+		(options.rules.size_x as u16 * 3 + 5) * 2
+	} else {
+		0
+	};
+	let min_width = options.rules.size_x as u16 * 3 * 2 + 11 + panel_width;
+	let min_height = 7 + 2 + 1 + options.rules.size_y as u16 + 1 + 7 + 5 + 3;
 	let size = termion::terminal_size();
 
 	if let Ok((width, height)) = size {
@@ -61,334 +160,367 @@ fn main() {
 		panic!("The size of the terminal can't be determined");
 	}
 
-	let mut stdout = stdout().into_raw_mode().unwrap();
-
-	// our boards
-	let mut board_me = [[Empty; config::SIZE_X]; config::SIZE_Y];
-	let mut board_ai = [[Empty; config::SIZE_X]; config::SIZE_Y];
-	let mut board_secret = [[Empty; config::SIZE_X]; config::SIZE_Y];
-
-	let mut history = History::new();
-
-	// let the AI generate their own ship placements
-	board_secret = ai::set_ships(board_secret);
-	let mut ai_attack = ai::Attack::new();
-
-	// the ships to be placed
-	let mut ships =
-		ShipTracker::new(config::SHIP_TWO_BLOCK_AMOUNT, config::SHIP_THREE_BLOCK_AMOUNT, config::SHIP_FOUR_BLOCK_AMOUNT);
-	let mut this_ship = ships.get_next_unset_ship().unwrap();
-	let mut ship_size = config::get_entitie_size(&this_ship);
+	// the AI or bot generates their own ship placements when the game is created
+	let created = match options.bot {
+		Some(command) => Game::with_strategy(options.rules, Box::new(Bot::new(&command, config::BOT_TIMEOUT))),
+		None => Game::new(options.rules),
+	};
+	let mut game = match created {
+		Ok(game) => game,
+		Err(error) => {
+			eprintln!("{}", cli::get_fleet_error(&error));
+			std::process::exit(1);
+		}
+	};
+
+	// a preset given on the command line is placed before the placing round starts so we can tweak it
+	let mut notice = String::new();
+	if let Some(name) = options.preset {
+		let loaded = preset::load(&presets_path).map_err(|error| gui::get_preset_error(&error)).and_then(|presets| {
+			match presets.iter().find(|preset| preset.name == name) {
+				Some(preset) => load_preset(&mut game, preset),
+				None => Err(format!("There is no layout named \"{}\" in {}", name, presets_path.display())),
+			}
+		});
 
-	// rotation of our ship
-	let mut rotation = Rotation::Horizontal;
+		if let Err(message) = loaded {
+			eprintln!("{}", message);
+			std::process::exit(1);
+		}
+		notice = format!("Loaded layout \"{}\"", name);
+	}
 
-	// our current position on the board
-	let mut pos_x: usize = 0;
-	let mut pos_y: usize = 0;
+	let mut stdout = stdout().into_raw_mode().unwrap();
+	let fleet_size = format!("{:0>2}", game.fleet_size());
 
-	// placing our first ship
-	board_me = movement::place_entity(board_me, pos_x, pos_y, ship_size, &rotation, Placeholder);
+	let mut history = History::new();
 
 	write!(stdout, "{}{}", termion::color::Bg(termion::color::Black), termion::clear::All).unwrap();
 	stdout.flush().unwrap();
 
 	gui::draw(
 		&mut stdout,
-		gui::get_score(board_me, board_ai, gui::Round::One),
-		gui::get_board(&board_me, &board_ai, pos_x, pos_y, gui::Round::One),
-		history.get_history(),
-		gui::get_round1_instructions(),
+		size_x,
+		gui::get_score(game.board_me(), game.board_ai(), gui::Round::One),
+		gui::get_board(game.board_me(), game.board_ai(), 0, 0, gui::Round::One, gui::Overlay::None, None),
+		gui::get_history(&history, size_x),
+		gui::get_round1_instructions(size_x, gui::Round::One, game.this_ship().is_none(), &notice),
 	);
 
-	let mut is_round_one_done = false;
-
 	// FIRST ROUND setting ships
+	// while picking up a placed ship a crosshair moves over our own board
+	let mut picking: Option<(usize, usize)> = None;
+	// while saving the layout every key goes into its name
+	let mut naming: Option<String> = None;
+	// the saved layout the next [l] loads
+	let mut preset_index: usize = 0;
 	for key in stdin().keys() {
-		match key.unwrap() {
-			Key::Esc | Key::Char('q') => {
-				write!(stdout, "{}{}", termion::cursor::Restore, termion::cursor::Show).unwrap();
-				stdout.flush().unwrap();
-				termion::raw::RawTerminal::suspend_raw_mode(&stdout).unwrap();
-				std::process::exit(0);
+		let key = key.unwrap();
+		notice = String::new();
+
+		let is_naming = naming.is_some();
+		if let Some(name) = naming.as_mut() {
+			match key {
+				Key::Esc => naming = None,
+				Key::Char('\n') if name.trim().is_empty() => notice = String::from("Type a name for the layout first"),
+				Key::Char('\n') => {
+					let preset = Preset::from_board(name.trim(), game.board_me());
+					notice = match preset::save(&presets_path, preset) {
+						Ok(()) => format!("Saved layout \"{}\" to {}", name.trim(), presets_path.display()),
+						Err(error) => gui::get_preset_error(&error),
+					};
+					naming = None;
+				}
+				Key::Backspace => {
+					name.pop();
+				}
+				// brackets mark the start of a preset in the file
+				Key::Char(letter)
+					if !letter.is_control()
+						&& letter != '['
+						&& letter != ']'
+						&& name.chars().count() < config::PRESET_NAME_LENGTH =>
+				{
+					name.push(letter)
+				}
+				_ => continue,
 			}
-			Key::Char('r') => {
-				let new_rotation = match rotation {
-					Rotation::Horizontal => Rotation::Vertical,
-					Rotation::Vertical => Rotation::Horizontal,
-				};
+		}
 
-				if movement::is_free_space(&board_me, pos_x as isize, pos_y as isize, ship_size, &new_rotation) {
-					// reset previous placement
-					board_me = movement::place_entity(board_me, pos_x, pos_y, ship_size, &rotation, Empty);
-					rotation = new_rotation;
-					// now place new ship in new rotation
-					board_me = movement::place_entity(board_me, pos_x, pos_y, ship_size, &rotation, Placeholder);
-				}
+		let command = match (picking, is_naming, key) {
+			(_, true, _) => None,
+			(_, _, Key::Esc) | (_, _, Key::Char('q')) => {
+				quit(&mut stdout, game.seed());
+				continue;
 			}
-			// PLACE SHIP
-			Key::Char('\n') => {
-				board_me = movement::place_entity(board_me, pos_x, pos_y, ship_size, &rotation, Cell::Ship);
-
-				ships.set_ship(&this_ship);
-				match ships.get_next_unset_ship() {
-					Some(kind) => {
-						this_ship = kind;
-						ship_size = config::get_entitie_size(&this_ship);
-						// collision detection for new pos_x and pos_y
-						let (x, y) = movement::get_next_available_coordinates(&board_me, ship_size, &rotation);
-						pos_x = x;
-						pos_y = y;
-						board_me = movement::place_entity(board_me, pos_x, pos_y, ship_size, &rotation, Placeholder);
-					}
-					None => {
-						is_round_one_done = true;
-					}
-				};
+			(Some(_), _, Key::Char('p')) => {
+				picking = None;
+				None
 			}
-			// MOVEMENT
-			Key::Left => {
-				let (board_new, pos_x_new, pos_y_new) =
-					movement::move_ship(board_me, pos_x, pos_y, ship_size, &rotation, Direction::Left);
-				board_me = board_new;
-				pos_x = pos_x_new;
-				pos_y = pos_y_new;
+			(Some((x, y)), _, Key::Left) => {
+				picking = Some((x.saturating_sub(1), y));
+				None
 			}
-			Key::Right => {
-				let (board_new, pos_x_new, pos_y_new) =
-					movement::move_ship(board_me, pos_x, pos_y, ship_size, &rotation, Direction::Right);
-				board_me = board_new;
-				pos_x = pos_x_new;
-				pos_y = pos_y_new;
+			(Some((x, y)), _, Key::Right) => {
+				picking = Some(((x + 1).min(game.rules().size_x - 1), y));
+				None
 			}
-			Key::Up => {
-				let (board_new, pos_x_new, pos_y_new) =
-					movement::move_ship(board_me, pos_x, pos_y, ship_size, &rotation, Direction::Up);
-				board_me = board_new;
-				pos_x = pos_x_new;
-				pos_y = pos_y_new;
+			(Some((x, y)), _, Key::Up) => {
+				picking = Some((x, y.saturating_sub(1)));
+				None
 			}
-			Key::Down => {
-				let (board_new, pos_x_new, pos_y_new) =
-					movement::move_ship(board_me, pos_x, pos_y, ship_size, &rotation, Direction::Down);
-				board_me = board_new;
-				pos_x = pos_x_new;
-				pos_y = pos_y_new;
+			(Some((x, y)), _, Key::Down) => {
+				picking = Some((x, (y + 1).min(game.rules().size_y - 1)));
+				None
 			}
-			Key::Delete | Key::Backspace => {
-				ships = ShipTracker::new(
-					config::SHIP_ONE_BLOCK_AMOUNT,
-					config::SHIP_TWO_BLOCK_AMOUNT,
-					config::SHIP_THREE_BLOCK_AMOUNT,
-				);
-				this_ship = ships.get_next_unset_ship().unwrap();
-				ship_size = config::get_entitie_size(&this_ship);
-				rotation = Rotation::Horizontal;
-				pos_x = 0;
-				pos_y = 0;
-				board_me = movement::place_entity(
-					[[Empty; config::SIZE_X]; config::SIZE_Y],
-					pos_x,
-					pos_y,
-					ship_size,
-					&rotation,
-					Placeholder,
-				);
+			(Some((pos_x, pos_y)), _, Key::Char('\n')) => Some(Command::PickUp { pos_x, pos_y }),
+			(Some(_), _, _) => continue,
+			(None, _, Key::Char('p')) => {
+				picking = Some(game.position());
+				None
 			}
-			_ => {}
+			(None, _, Key::Char('s')) if game.this_ship().is_none() => {
+				naming = Some(String::new());
+				None
+			}
+			(None, _, Key::Char('s')) => {
+				notice = String::from("Place all your ships before saving the layout");
+				None
+			}
+			(None, _, Key::Char('l')) => {
+				notice = match preset::load(&presets_path) {
+					Ok(presets) if presets.is_empty() => {
+						String::from("There are no saved layouts yet, place your fleet and save it with [s]")
+					}
+					Ok(presets) => {
+						let index = preset_index % presets.len();
+						preset_index = index + 1;
+						match load_preset(&mut game, &presets[index]) {
+							Ok(()) => {
+								format!("Loaded layout \"{}\" ({} of {})", presets[index].name, index + 1, presets.len())
+							}
+							Err(message) => message,
+						}
+					}
+					Err(error) => gui::get_preset_error(&error),
+				};
+				None
+			}
+			(None, _, Key::Char('u')) => Some(Command::Undo),
+			(None, _, Key::Char('f')) => Some(Command::Randomise),
+			(None, _, Key::Char('r')) => Some(Command::Rotate),
+			// once every ship is placed enter confirms the fleet
+			(None, _, Key::Char('\n')) if game.this_ship().is_none() => Some(Command::Confirm),
+			(None, _, Key::Char('\n')) => Some(Command::PlaceShip),
+			(None, _, Key::Left) => Some(Command::Move(Direction::Left)),
+			(None, _, Key::Right) => Some(Command::Move(Direction::Right)),
+			(None, _, Key::Up) => Some(Command::Move(Direction::Up)),
+			(None, _, Key::Down) => Some(Command::Move(Direction::Down)),
+			(None, _, Key::Delete) | (None, _, Key::Backspace) => Some(Command::ResetPlacement),
+			_ => continue,
+		};
+
+		// moves that are not possible leave the ship where it is and tell us why
+		if let Some(command) = command {
+			notice = match (command, game.execute(command)) {
+				(_, Err(engine::CommandError::InvalidPlacement(error))) => gui::get_placement_error(&error, game.board_me()),
+				(Command::PickUp { pos_x, pos_y }, Err(engine::CommandError::NoShip)) => {
+					format!("There is no ship at {} to pick up", gui::get_coord(pos_x, pos_y))
+				}
+				(Command::Undo, Err(engine::CommandError::NoShip)) => String::from("There is no ship to undo yet"),
+				(_, Err(engine::CommandError::Fleet(error))) => cli::get_fleet_error(&error),
+				(_, Ok(engine::Outcome::PickedUp(_))) => {
+					picking = None;
+					String::new()
+				}
+				_ => String::new(),
+			};
 		}
 
+		let (round, cursor_x, cursor_y) = match (picking, &naming) {
+			(_, Some(name)) => {
+				notice = format!("Save layout as: {}_", name);
+				(gui::Round::Name, 0, 0)
+			}
+			(Some((x, y)), None) => (gui::Round::Pick, x, y),
+			(None, None) => (gui::Round::One, 0, 0),
+		};
 		gui::draw(
 			&mut stdout,
-			gui::get_score(board_me, board_ai, gui::Round::One),
-			gui::get_board(&board_me, &board_ai, pos_x, pos_y, gui::Round::One),
-			history.get_history(),
-			gui::get_round1_instructions(),
+			size_x,
+			gui::get_score(game.board_me(), game.board_ai(), gui::Round::One),
+			gui::get_board(game.board_me(), game.board_ai(), cursor_x, cursor_y, round, gui::Overlay::None, None),
+			gui::get_history(&history, size_x),
+			gui::get_round1_instructions(size_x, round, game.this_ship().is_none(), &notice),
 		);
 
-		if is_round_one_done {
+		if game.phase() != Phase::Placement {
 			break;
 		}
 	}
 
-	pos_x = 0;
-	pos_y = 0;
+	// our current crosshair position on the board
+	let mut pos_x: usize = 0;
+	let mut pos_y: usize = 0;
+	// the last hint stays on the board until we shoot, the heatmap shades the whole board with it
+	let mut hint: Option<engine::Hint> = None;
+	let mut show_heatmap = false;
+	// every shot I fire in order, the profile keeps the first of them
+	let mut my_shots: Vec<(usize, usize)> = Vec::new();
+	// what the AI weighs for its next shot, it only changes when the AI shoots
+	let mut insight = if debug { game.explain_ai_turn() } else { None };
 	history.set_history("Placed ships", history::Actor::Me);
 	history.set_history("Placed ships", history::Actor::Ai);
 
 	gui::draw(
 		&mut stdout,
-		gui::get_score(board_me, board_ai, gui::Round::Two),
-		gui::get_board(&board_me, &board_ai, pos_x, pos_y, gui::Round::Two),
-		history.get_history(),
-		gui::get_round2_instructions(),
+		size_x,
+		gui::get_score(game.board_me(), game.board_ai(), gui::Round::Two),
+		gui::get_board(
+			game.board_me(),
+			game.board_ai(),
+			pos_x,
+			pos_y,
+			gui::Round::Two,
+			gui::Overlay::None,
+			insight.as_ref(),
+		),
+		gui::get_history(&history, size_x),
+		gui::get_round2_instructions(size_x, &fleet_size, game.hints_left()),
 	);
 
-	let mut is_round_two_done = false;
-
 	// SECOND ROUND shooting turns
-	for key in stdin().keys() {
+	let mut keys = stdin().keys();
+	while let Some(key) = keys.next() {
 		match key.unwrap() {
 			Key::Esc | Key::Char('q') => {
-				write!(stdout, "{}{}", termion::cursor::Restore, termion::cursor::Show).unwrap();
-				stdout.flush().unwrap();
-				termion::raw::RawTerminal::suspend_raw_mode(&stdout).unwrap();
-				std::process::exit(0);
+				quit(&mut stdout, game.seed());
 			}
 			// SHOOT
 			Key::Char('\n') => {
-				if movement::is_free_space(&board_ai, pos_x as isize, pos_y as isize, 1, &Rotation::Horizontal) {
-					let mut ai_move = false;
-					let hit_type = game::get_hit_type(&board_ai, &board_secret, pos_x, pos_y);
-					match hit_type {
-						game::HitType::Hit => {
-							history
-								.set_history(&format!("Shoot at {} and hit a ship", gui::get_coord(pos_x, pos_y)), history::Actor::Me);
-							board_ai = movement::place_entity(board_ai, pos_x, pos_y, 1, &Rotation::Horizontal, Damage);
-						}
-						game::HitType::HitNSunk => {
-							history.set_history(
-								&format!("Shoot at {} and hit and sunk a ship", gui::get_coord(pos_x, pos_y)),
-								history::Actor::Me,
+				if let Ok(engine::Outcome::Shot(shot)) = game.execute(Command::Fire { pos_x, pos_y }) {
+					log_shot(&mut history, &shot);
+					my_shots.push((shot.pos_x, shot.pos_y));
+					hint = None;
+
+					// AI SHOTS, the first comes right away and every shot after a hit with a pause, while debugging
+					// each waits for a key instead
+					let mut is_first_ai_shot = true;
+					while game.phase() == Phase::Battle && game.turn() == history::Actor::Ai {
+						if debug {
+							gui::draw(
+								&mut stdout,
+								size_x,
+								gui::get_score(game.board_me(), game.board_ai(), gui::Round::Two),
+								gui::get_board(
+									game.board_me(),
+									game.board_ai(),
+									pos_x,
+									pos_y,
+									gui::Round::One,
+									gui::Overlay::None,
+									insight.as_ref(),
+								),
+								gui::get_history(&history, size_x),
+								gui::get_step_instructions(size_x),
 							);
-							board_ai = movement::place_entity(board_ai, pos_x, pos_y, 1, &Rotation::Horizontal, Damage);
-							ai_move = true;
-						}
-						game::HitType::Miss => {
-							history.set_history(&format!("Shoot at {} and missed", gui::get_coord(pos_x, pos_y)), history::Actor::Me);
-							board_ai = movement::place_entity(board_ai, pos_x, pos_y, 1, &Rotation::Horizontal, Shot);
-							ai_move = true;
+							if let None | Some(Ok(Key::Esc | Key::Char('q'))) = keys.next() {
+								quit(&mut stdout, game.seed());
+							}
+						} else if !is_first_ai_shot {
+							thread::sleep(time::Duration::from_millis(2000));
 						}
-					};
+						is_first_ai_shot = false;
 
-					// AI FIRST SHOT
-					if ai_move {
-						let mut another_turn = false;
-						let (ai_pos_x, ai_pos_y) = ai_attack.shoot(&board_me);
-						let hit_type = game::get_hit_type(&board_me, &board_me, ai_pos_x, ai_pos_y);
-
-						match hit_type {
-							game::HitType::Hit => {
-								history.set_history(
-									&format!("Shoot at {} and hit a ship", gui::get_coord(ai_pos_x, ai_pos_y)),
-									history::Actor::Ai,
-								);
-								board_me = movement::place_entity(board_me, ai_pos_x, ai_pos_y, 1, &Rotation::Horizontal, Damage);
-								another_turn = true;
-							}
-							game::HitType::HitNSunk => {
-								history.set_history(
-									&format!("Shoot at {} and hit and sunk a ship", gui::get_coord(ai_pos_x, ai_pos_y)),
-									history::Actor::Ai,
-								);
-								board_me = movement::place_entity(board_me, ai_pos_x, ai_pos_y, 1, &Rotation::Horizontal, Damage);
-							}
-							game::HitType::Miss => {
-								history.set_history(
-									&format!("Shoot at {} and missed", gui::get_coord(ai_pos_x, ai_pos_y)),
-									history::Actor::Ai,
-								);
-								board_me = movement::place_entity(board_me, ai_pos_x, ai_pos_y, 1, &Rotation::Horizontal, Shot);
+						match game.play_ai_turn() {
+							Ok(shot) => log_shot(&mut history, &shot),
+							Err(engine::CommandError::Forfeit(reason)) => {
+								history.set_history(&format!("Gave up. {}", reason), history::Actor::Ai);
 							}
-						};
+							Err(_) => {}
+						}
+						if debug {
+							insight = game.explain_ai_turn();
+						}
 
 						gui::draw(
 							&mut stdout,
-							gui::get_score(board_me, board_ai, gui::Round::Two),
-							gui::get_board(&board_me, &board_ai, pos_x, pos_y, gui::Round::One),
-							history.get_history(),
-							gui::get_round2_instructions(),
+							size_x,
+							gui::get_score(game.board_me(), game.board_ai(), gui::Round::Two),
+							gui::get_board(
+								game.board_me(),
+								game.board_ai(),
+								pos_x,
+								pos_y,
+								gui::Round::One,
+								gui::Overlay::None,
+								insight.as_ref(),
+							),
+							gui::get_history(&history, size_x),
+							gui::get_round2_instructions(size_x, &fleet_size, game.hints_left()),
 						);
-
-						// AI SHOT AFTER HIT
-						while another_turn {
-							thread::sleep(time::Duration::from_millis(2000));
-
-							let (ai_pos_x, ai_pos_y) = ai_attack.shoot_after_hit(&board_me);
-							let hit_type = game::get_hit_type(&board_me, &board_me, ai_pos_x, ai_pos_y);
-
-							match hit_type {
-								game::HitType::Hit => {
-									history.set_history(
-										&format!("Shoot at {} and hit a ship", gui::get_coord(ai_pos_x, ai_pos_y)),
-										history::Actor::Ai,
-									);
-									board_me = movement::place_entity(board_me, ai_pos_x, ai_pos_y, 1, &Rotation::Horizontal, Damage);
-								}
-								game::HitType::HitNSunk => {
-									history.set_history(
-										&format!("Shoot at {} and hit and sunk a ship", gui::get_coord(ai_pos_x, ai_pos_y)),
-										history::Actor::Ai,
-									);
-									board_me = movement::place_entity(board_me, ai_pos_x, ai_pos_y, 1, &Rotation::Horizontal, Damage);
-									another_turn = false;
-								}
-								game::HitType::Miss => {
-									history.set_history(
-										&format!("Shoot at {} and missed", gui::get_coord(ai_pos_x, ai_pos_y)),
-										history::Actor::Ai,
-									);
-									board_me = movement::place_entity(board_me, ai_pos_x, ai_pos_y, 1, &Rotation::Horizontal, Shot);
-									another_turn = false;
-								}
-							};
-
-							gui::draw(
-								&mut stdout,
-								gui::get_score(board_me, board_ai, gui::Round::Two),
-								gui::get_board(&board_me, &board_ai, pos_x, pos_y, gui::Round::One),
-								history.get_history(),
-								gui::get_round2_instructions(),
-							);
-						}
 					}
 
-					let (x, y) = movement::get_next_available_coordinates(&board_ai, 1, &Rotation::Horizontal);
-					pos_x = x;
-					pos_y = y;
-
-					let score_me = game::get_score(&board_ai);
-					let score_ai = game::get_score(&board_me);
-
-					if score_me == *"10" || score_ai == *"10" {
-						is_round_two_done = true;
+					if let Some((x, y)) =
+						movement::get_next_available_coordinates(game.board_ai(), 1, &Rotation::Horizontal, Adjacency::Any)
+					{
+						pos_x = x;
+						pos_y = y;
 					}
 				}
 			}
-			// MOVEMENT
-			Key::Left => {
-				if pos_x > 0 {
-					pos_x -= 1;
+			// HINTS
+			Key::Char('h') => match game.execute(Command::Hint) {
+				Ok(engine::Outcome::Hint(new_hint)) => {
+					let entry = format!(
+						"Asked for a hint, it points at {} ({} left)",
+						gui::get_coord(new_hint.pos_x, new_hint.pos_y),
+						game.hints_left()
+					);
+					history.set_history(&entry, history::Actor::Me);
+					hint = Some(new_hint);
 				}
-			}
-			Key::Right => {
-				if pos_x < (config::SIZE_X - 1) {
-					pos_x += 1;
-				}
-			}
-			Key::Up => {
-				if pos_y > 0 {
-					pos_y -= 1;
-				}
-			}
-			Key::Down => {
-				if pos_y < (config::SIZE_Y - 1) {
-					pos_y += 1;
+				Err(engine::CommandError::NoHintsLeft) => {
+					history.set_history("Asked for a hint but there are none left", history::Actor::Me);
 				}
+				_ => {}
+			},
+			Key::Char('m') => {
+				show_heatmap = !show_heatmap;
 			}
+			// MOVEMENT
+			Key::Left => pos_x = pos_x.saturating_sub(1),
+			Key::Right if pos_x < (game.rules().size_x - 1) => pos_x += 1,
+			Key::Up => pos_y = pos_y.saturating_sub(1),
+			Key::Down if pos_y < (game.rules().size_y - 1) => pos_y += 1,
 			_ => {}
 		}
 
+		let overlay = match (&hint, show_heatmap) {
+			(Some(hint), true) => gui::Overlay::Heatmap(hint),
+			(Some(hint), false) => gui::Overlay::Hint(hint),
+			(None, _) => gui::Overlay::None,
+		};
 		gui::draw(
 			&mut stdout,
-			gui::get_score(board_me, board_ai, gui::Round::Two),
-			gui::get_board(&board_me, &board_ai, pos_x, pos_y, gui::Round::Two),
-			history.get_history(),
-			gui::get_round2_instructions(),
+			size_x,
+			gui::get_score(game.board_me(), game.board_ai(), gui::Round::Two),
+			gui::get_board(game.board_me(), game.board_ai(), pos_x, pos_y, gui::Round::Two, overlay, insight.as_ref()),
+			gui::get_history(&history, size_x),
+			gui::get_round2_instructions(size_x, &fleet_size, game.hints_left()),
 		);
 
-		if is_round_two_done {
-			write!(stdout, "{}\r\n", gui::get_good_bye_msg(game::get_score(&board_ai) == *"10")).unwrap();
+		if let Phase::Finished(winner) = game.phase() {
+			write!(stdout, "{}\r\n", gui::get_good_bye_msg(winner == history::Actor::Me, size_x, game.seed())).unwrap();
+
+			// every finished game teaches the adaptive AI a bit more about how I play
+			let saved = profile::load(&profile_path).and_then(|mut profile| {
+				profile.record(game.board_me(), &my_shots);
+				profile::save(&profile_path, &profile)
+			});
+			if let Err(error) = saved {
+				write!(stdout, "{}{}\r\n", gui::get_padding(size_x), gui::get_profile_error(&error, &profile_path)).unwrap();
+			}
 			break;
 		}
 	}
@@ -1,18 +1,22 @@
-use crate::config;
+//! Placing and moving ships and crosshairs on a board
+
+use crate::board::Board;
+use crate::ships::{ShipClass, ShipId};
 use crate::Cell;
 use crate::Direction;
 use crate::Rotation;
 
-use Cell::{Crosshair, Empty, Placeholder, ShipFour, ShipThree, ShipTwo};
+use Cell::{Crosshair, Empty, Placeholder};
 
+/// Move the placeholder of a ship one cell into a direction if there is space
 pub fn move_ship(
-	mut board: config::Board,
+	mut board: Board,
 	mut pos_x: usize,
 	mut pos_y: usize,
 	ship_size: usize,
 	rotation: &Rotation,
 	direction: Direction,
-) -> (config::Board, usize, usize) {
+) -> (Board, usize, usize) {
 	match direction {
 		Direction::Left => {
 			if is_free_space(&board, pos_x as isize - 1, pos_y as isize, ship_size, rotation) {
@@ -57,92 +61,93 @@ pub fn move_ship(
 
 #[test]
 fn move_ship_works() {
-	let limit_x = config::SIZE_X - 1;
-	let limit_y = config::SIZE_Y - 1;
+	let limit_x = Board::default().size_x() - 1;
+	let limit_y = Board::default().size_y() - 1;
 
-	let mut board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	let mut board = Board::default();
 	board[0][0] = Placeholder;
-	let mut result = move_ship(board, 0, 0, 1, &Rotation::Horizontal, Direction::Right);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	let mut result = move_ship(board.clone(), 0, 0, 1, &Rotation::Horizontal, Direction::Right);
+	board = Board::default();
 	board[0][1] = Placeholder;
 	assert_eq!(result, (board, 1, 0));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[0][0] = Placeholder;
-	result = move_ship(board, 0, 0, 1, &Rotation::Horizontal, Direction::Left);
+	result = move_ship(board.clone(), 0, 0, 1, &Rotation::Horizontal, Direction::Left);
 	assert_eq!(result, (board, 0, 0));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[0][0] = Placeholder;
-	result = move_ship(board, 0, 0, 1, &Rotation::Horizontal, Direction::Up);
+	result = move_ship(board.clone(), 0, 0, 1, &Rotation::Horizontal, Direction::Up);
 	assert_eq!(result, (board, 0, 0));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[0][0] = Placeholder;
-	result = move_ship(board, 0, 0, 1, &Rotation::Horizontal, Direction::Down);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_ship(board.clone(), 0, 0, 1, &Rotation::Horizontal, Direction::Down);
+	board = Board::default();
 	board[1][0] = Placeholder;
 	assert_eq!(result, (board, 0, 1));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[5][5] = Placeholder;
-	result = move_ship(board, 5, 5, 1, &Rotation::Horizontal, Direction::Right);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_ship(board.clone(), 5, 5, 1, &Rotation::Horizontal, Direction::Right);
+	board = Board::default();
 	board[5][6] = Placeholder;
 	assert_eq!(result, (board, 6, 5));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[5][5] = Placeholder;
-	result = move_ship(board, 5, 5, 1, &Rotation::Horizontal, Direction::Left);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_ship(board.clone(), 5, 5, 1, &Rotation::Horizontal, Direction::Left);
+	board = Board::default();
 	board[5][4] = Placeholder;
 	assert_eq!(result, (board, 4, 5));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[5][5] = Placeholder;
-	result = move_ship(board, 5, 5, 1, &Rotation::Horizontal, Direction::Up);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_ship(board.clone(), 5, 5, 1, &Rotation::Horizontal, Direction::Up);
+	board = Board::default();
 	board[4][5] = Placeholder;
 	assert_eq!(result, (board, 5, 4));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[5][5] = Placeholder;
-	result = move_ship(board, 5, 5, 1, &Rotation::Horizontal, Direction::Down);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_ship(board.clone(), 5, 5, 1, &Rotation::Horizontal, Direction::Down);
+	board = Board::default();
 	board[6][5] = Placeholder;
 	assert_eq!(result, (board, 5, 6));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
-	board[limit_y][limit_y] = Placeholder;
-	result = move_ship(board, limit_x, limit_y, 1, &Rotation::Horizontal, Direction::Right);
+	board = Board::default();
+	board[limit_y][limit_x] = Placeholder;
+	result = move_ship(board.clone(), limit_x, limit_y, 1, &Rotation::Horizontal, Direction::Right);
 	assert_eq!(result, (board, limit_x, limit_y));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[limit_y][limit_x] = Placeholder;
-	result = move_ship(board, limit_x, limit_y, 1, &Rotation::Horizontal, Direction::Left);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_ship(board.clone(), limit_x, limit_y, 1, &Rotation::Horizontal, Direction::Left);
+	board = Board::default();
 	board[limit_y][limit_x - 1] = Placeholder;
 	assert_eq!(result, (board, limit_x - 1, limit_y));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[limit_y][limit_x] = Placeholder;
-	result = move_ship(board, limit_x, limit_y, 1, &Rotation::Horizontal, Direction::Up);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_ship(board.clone(), limit_x, limit_y, 1, &Rotation::Horizontal, Direction::Up);
+	board = Board::default();
 	board[limit_y - 1][limit_x] = Placeholder;
 	assert_eq!(result, (board, limit_x, limit_y - 1));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[limit_y][limit_x] = Placeholder;
-	result = move_ship(board, limit_x, limit_y, 1, &Rotation::Horizontal, Direction::Down);
+	result = move_ship(board.clone(), limit_x, limit_y, 1, &Rotation::Horizontal, Direction::Down);
 	assert_eq!(result, (board, limit_x, limit_y));
 }
 
+/// Move the crosshair to the next free cell into a direction
 pub fn move_crosshair(
-	mut board: config::Board,
+	mut board: Board,
 	mut pos_x: usize,
 	mut pos_y: usize,
 	direction: Direction,
-) -> (config::Board, usize, usize) {
+) -> (Board, usize, usize) {
 	let bound_x = pos_x;
 	let bound_y = pos_y;
 	match direction {
@@ -160,7 +165,7 @@ pub fn move_crosshair(
 			}
 		}
 		Direction::Right => {
-			for i in bound_x..10 {
+			for i in bound_x..board.size_x() {
 				let new_pos_x = i as isize;
 				if is_free_space(&board, new_pos_x, pos_y as isize, 1, &Rotation::Horizontal) {
 					// clear previous position
@@ -186,7 +191,7 @@ pub fn move_crosshair(
 			}
 		}
 		Direction::Down => {
-			for i in bound_y..10 {
+			for i in bound_y..board.size_y() {
 				let new_pos_y = i as isize;
 				if is_free_space(&board, pos_x as isize, new_pos_y, 1, &Rotation::Horizontal) {
 					// clear previous position
@@ -205,143 +210,118 @@ pub fn move_crosshair(
 
 #[test]
 fn move_crosshair_works() {
-	let mut board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	let mut board = Board::default();
 	board[0][0] = Crosshair;
-	let mut result = move_crosshair(board, 0, 0, Direction::Right);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	let mut result = move_crosshair(board.clone(), 0, 0, Direction::Right);
+	board = Board::default();
 	board[0][1] = Crosshair;
 	assert_eq!(result, (board, 1, 0));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[0][0] = Crosshair;
-	result = move_crosshair(board, 0, 0, Direction::Left);
+	result = move_crosshair(board.clone(), 0, 0, Direction::Left);
 	assert_eq!(result, (board, 0, 0));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[0][0] = Crosshair;
-	result = move_crosshair(board, 0, 0, Direction::Up);
+	result = move_crosshair(board.clone(), 0, 0, Direction::Up);
 	assert_eq!(result, (board, 0, 0));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[0][0] = Crosshair;
-	result = move_crosshair(board, 0, 0, Direction::Down);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_crosshair(board.clone(), 0, 0, Direction::Down);
+	board = Board::default();
 	board[1][0] = Crosshair;
 	assert_eq!(result, (board, 0, 1));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[5][5] = Crosshair;
-	result = move_crosshair(board, 5, 5, Direction::Right);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_crosshair(board.clone(), 5, 5, Direction::Right);
+	board = Board::default();
 	board[5][6] = Crosshair;
 	assert_eq!(result, (board, 6, 5));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[5][5] = Crosshair;
-	result = move_crosshair(board, 5, 5, Direction::Left);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_crosshair(board.clone(), 5, 5, Direction::Left);
+	board = Board::default();
 	board[5][4] = Crosshair;
 	assert_eq!(result, (board, 4, 5));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[5][5] = Crosshair;
-	result = move_crosshair(board, 5, 5, Direction::Up);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_crosshair(board.clone(), 5, 5, Direction::Up);
+	board = Board::default();
 	board[4][5] = Crosshair;
 	assert_eq!(result, (board, 5, 4));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[5][5] = Crosshair;
-	result = move_crosshair(board, 5, 5, Direction::Down);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_crosshair(board.clone(), 5, 5, Direction::Down);
+	board = Board::default();
 	board[6][5] = Crosshair;
 	assert_eq!(result, (board, 5, 6));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[9][9] = Crosshair;
-	result = move_crosshair(board, 9, 9, Direction::Right);
+	result = move_crosshair(board.clone(), 9, 9, Direction::Right);
 	assert_eq!(result, (board, 9, 9));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[9][9] = Crosshair;
-	result = move_crosshair(board, 9, 9, Direction::Left);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_crosshair(board.clone(), 9, 9, Direction::Left);
+	board = Board::default();
 	board[9][8] = Crosshair;
 	assert_eq!(result, (board, 8, 9));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[9][9] = Crosshair;
-	result = move_crosshair(board, 9, 9, Direction::Up);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = move_crosshair(board.clone(), 9, 9, Direction::Up);
+	board = Board::default();
 	board[8][9] = Crosshair;
 	assert_eq!(result, (board, 9, 8));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	board = Board::default();
 	board[9][9] = Crosshair;
-	result = move_crosshair(board, 9, 9, Direction::Down);
+	result = move_crosshair(board.clone(), 9, 9, Direction::Down);
 	assert_eq!(result, (board, 9, 9));
+
+	board = Board::new(15, 12);
+	board[11][10] = Crosshair;
+	result = move_crosshair(board.clone(), 10, 11, Direction::Right);
+	board = Board::new(15, 12);
+	board[11][11] = Crosshair;
+	assert_eq!(result, (board, 11, 11));
+
+	board = Board::new(15, 12);
+	board[10][14] = Crosshair;
+	result = move_crosshair(board.clone(), 14, 10, Direction::Down);
+	board = Board::new(15, 12);
+	board[11][14] = Crosshair;
+	assert_eq!(result, (board, 14, 11));
+}
+
+/// The cells a ship of this size would take up at this position
+pub fn get_coordinates(pos_x: usize, pos_y: usize, ship_size: usize, rotation: &Rotation) -> Vec<(usize, usize)> {
+	(0..ship_size)
+		.map(|offset| match rotation {
+			Rotation::Horizontal => (pos_x + offset, pos_y),
+			Rotation::Vertical => (pos_x, pos_y + offset),
+		})
+		.collect()
 }
 
+/// Set all cells a ship of this size would take up to `cell`
 pub fn place_entity(
-	mut board: config::Board,
+	mut board: Board,
 	pos_x: usize,
 	pos_y: usize,
 	ship_size: usize,
 	rotation: &Rotation,
 	cell: Cell,
-) -> config::Board {
-	let mut coords: Vec<u8> = vec![];
-
-	match rotation {
-		Rotation::Horizontal => {
-			for offset in 0..ship_size {
-				coords.push(pos_x as u8 + offset as u8);
-				coords.push(pos_y as u8);
-			}
-		}
-		Rotation::Vertical => {
-			for offset in 0..ship_size {
-				coords.push(pos_x as u8);
-				coords.push(pos_y as u8 + offset as u8);
-			}
-		}
-	}
-
-	let mut i = 0;
-	while i < coords.len() {
-		let x = coords[i] as usize;
-		i += 1;
-		let y = coords[i] as usize;
-		i += 1;
-
-		board[y][x] = match cell {
-			Cell::Ship => match ship_size {
-				2 => ShipTwo([
-					coords[0] as usize,
-					coords[1] as usize,
-					coords[2] as usize,
-					coords[3] as usize,
-				]),
-				3 => ShipThree([
-					coords[0] as usize,
-					coords[1] as usize,
-					coords[2] as usize,
-					coords[3] as usize,
-					coords[4] as usize,
-					coords[5] as usize,
-				]),
-				_ => ShipFour([
-					coords[0] as usize,
-					coords[1] as usize,
-					coords[2] as usize,
-					coords[3] as usize,
-					coords[4] as usize,
-					coords[5] as usize,
-					coords[6] as usize,
-					coords[7] as usize,
-			},
-			_ => cell,
-		};
+) -> Board {
+	for (x, y) in get_coordinates(pos_x, pos_y, ship_size, rotation) {
+		board[y][x] = cell;
 	}
 
 	board
@@ -349,162 +329,354 @@ pub fn place_entity(
 
 #[test]
 fn place_entity_works() {
-	let mut result = place_entity([[Empty; config::SIZE_X]; config::SIZE_Y], 0, 0, 1, &Rotation::Horizontal, Placeholder);
-	let mut board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	let mut result = place_entity(Board::default(), 0, 0, 1, &Rotation::Horizontal, Placeholder);
+	let mut board = Board::default();
 	board[0][0] = Placeholder;
 	assert_eq!(result, board);
 
-	result = place_entity([[Empty; config::SIZE_X]; config::SIZE_Y], 0, 0, 2, &Rotation::Horizontal, Placeholder);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = place_entity(Board::default(), 0, 0, 2, &Rotation::Horizontal, Placeholder);
+	board = Board::default();
 	board[0][0] = Placeholder;
 	board[0][1] = Placeholder;
 	assert_eq!(result, board);
 
-	result = place_entity([[Empty; config::SIZE_X]; config::SIZE_Y], 0, 0, 3, &Rotation::Horizontal, Placeholder);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = place_entity(Board::default(), 0, 0, 3, &Rotation::Horizontal, Placeholder);
+	board = Board::default();
 	board[0][0] = Placeholder;
 	board[0][1] = Placeholder;
 	board[0][2] = Placeholder;
 	assert_eq!(result, board);
 
-	result = place_entity([[Empty; config::SIZE_X]; config::SIZE_Y], 0, 0, 1, &Rotation::Vertical, Placeholder);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = place_entity(Board::default(), 0, 0, 1, &Rotation::Vertical, Placeholder);
+	board = Board::default();
 	board[0][0] = Placeholder;
 	assert_eq!(result, board);
 
-	result = place_entity([[Empty; config::SIZE_X]; config::SIZE_Y], 0, 0, 2, &Rotation::Vertical, Placeholder);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = place_entity(Board::default(), 0, 0, 2, &Rotation::Vertical, Placeholder);
+	board = Board::default();
 	board[0][0] = Placeholder;
 	board[1][0] = Placeholder;
 	assert_eq!(result, board);
 
-	result = place_entity([[Empty; config::SIZE_X]; config::SIZE_Y], 0, 0, 3, &Rotation::Vertical, Placeholder);
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	result = place_entity(Board::default(), 0, 0, 3, &Rotation::Vertical, Placeholder);
+	board = Board::default();
 	board[0][0] = Placeholder;
 	board[1][0] = Placeholder;
 	board[2][0] = Placeholder;
 	assert_eq!(result, board);
 }
 
-pub fn is_free_space(
-	board: &config::Board,
+/// Register a new ship in the boards fleet and point its cells to it
+pub fn place_ship(mut board: Board, pos_x: usize, pos_y: usize, kind: &ShipClass, rotation: &Rotation) -> Board {
+	let coords = get_coordinates(pos_x, pos_y, kind.size, rotation);
+	let id = board.fleet_mut().add(kind, coords);
+
+	place_entity(board, pos_x, pos_y, kind.size, rotation, Cell::Ship(id))
+}
+
+#[test]
+fn place_ship_works() {
+	let destroyer = ShipClass::new("Destroyer", 2, 1);
+	let carrier = ShipClass::new("Carrier", 5, 1);
+
+	let mut board = place_ship(Board::default(), 1, 1, &destroyer, &Rotation::Horizontal);
+	board = place_ship(board, 0, 3, &carrier, &Rotation::Vertical);
+
+	let ships = board.fleet().get_ships();
+	assert_eq!(ships.len(), 2);
+	assert_eq!(ships[0].cells, vec![(1, 1), (2, 1)]);
+	assert_eq!(ships[1].cells, vec![(0, 3), (0, 4), (0, 5), (0, 6), (0, 7)]);
+
+	assert_eq!(board[1][1], Cell::Ship(ships[0].id));
+	assert_eq!(board[1][2], Cell::Ship(ships[0].id));
+	assert_eq!(board[1][3], Empty);
+	for y in 3..8 {
+		assert_eq!(board[y][0], Cell::Ship(ships[1].id));
+	}
+	assert_eq!(board[8][0], Empty);
+}
+
+/// The edge of the board a ship would cross
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Edge {
+	Left,
+	Right,
+	Top,
+	Bottom,
+}
+
+/// How close ships may be placed to each other
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Adjacency {
+	/// Ships may touch in any way
+	#[default]
+	Any,
+	/// Ships may only touch at their corners
+	NoEdge,
+	/// Ships may not touch at all, not even at their corners
+	NoEdgeOrCorner,
+}
+
+/// Why a ship can't be placed at a position
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PlacementError {
+	/// The ship would stick out over this edge of the board
+	OutOfBounds(Edge),
+	/// The ship would lie on top of the ship with this id
+	Overlap(ShipId),
+	/// The ship would lie on a cell that is already taken by something other than a ship e.g. a shot
+	Occupied { pos_x: usize, pos_y: usize },
+	/// The ship would touch the ship with this id which the adjacency rule forbids
+	Adjacent(ShipId),
+}
+
+/// Check whether a ship fits at this position without leaving the board, overlapping another ship or breaking the
+/// adjacency rule
+pub fn check_placement(
+	board: &Board,
 	temp_pos_x: isize,
 	temp_pos_y: isize,
 	ship_size: usize,
 	rotation: &Rotation,
-) -> bool {
-	let mut result = true;
-
-	if temp_pos_x < 0
-		|| temp_pos_y < 0
-		|| temp_pos_x > (config::SIZE_X as isize - 1)
-		|| temp_pos_y > (config::SIZE_Y as isize - 1)
-	{
-		return false;
+	adjacency: Adjacency,
+) -> Result<(), PlacementError> {
+	if temp_pos_x < 0 {
+		return Err(PlacementError::OutOfBounds(Edge::Left));
+	}
+	if temp_pos_y < 0 {
+		return Err(PlacementError::OutOfBounds(Edge::Top));
 	}
 
 	let pos_x: usize = temp_pos_x as usize;
 	let pos_y: usize = temp_pos_y as usize;
+	let (end_x, end_y) = match rotation {
+		Rotation::Horizontal => (pos_x + ship_size.max(1) - 1, pos_y),
+		Rotation::Vertical => (pos_x, pos_y + ship_size.max(1) - 1),
+	};
 
-	match rotation {
-		Rotation::Horizontal => {
-			for offset in 0..ship_size {
-				let new_pos_x = pos_x + offset;
-				if new_pos_x > (config::SIZE_X - 1)
-					|| pos_y > (config::SIZE_Y - 1)
-					|| board[pos_y][new_pos_x] != Empty && board[pos_y][new_pos_x] != Placeholder
-				{
-					result = false;
-					break;
-				}
+	if end_x > board.size_x() - 1 {
+		return Err(PlacementError::OutOfBounds(Edge::Right));
+	}
+	if end_y > board.size_y() - 1 {
+		return Err(PlacementError::OutOfBounds(Edge::Bottom));
+	}
+
+	for (new_pos_x, new_pos_y) in get_coordinates(pos_x, pos_y, ship_size, rotation) {
+		match board[new_pos_y][new_pos_x] {
+			Empty | Placeholder => {}
+			Cell::Ship(id) => return Err(PlacementError::Overlap(id)),
+			_ => {
+				return Err(PlacementError::Occupied {
+					pos_x: new_pos_x,
+					pos_y: new_pos_y,
+				})
 			}
 		}
-		Rotation::Vertical => {
-			for offset in 0..ship_size {
-				let new_pos_y = pos_y + offset;
-				if pos_x > (config::SIZE_X - 1)
-					|| new_pos_y > (config::SIZE_Y - 1)
-					|| board[new_pos_y][pos_x] != Empty && board[new_pos_y][pos_x] != Placeholder
-				{
-					result = false;
-					break;
-				}
-			}
+	}
+
+	let coordinates = get_coordinates(pos_x, pos_y, ship_size, rotation);
+	for (x, y) in get_surroundings(board, &coordinates, adjacency) {
+		if let Cell::Ship(id) = board[y][x] {
+			return Err(PlacementError::Adjacent(id));
 		}
+	}
+
+	Ok(())
+}
+
+/// The cells around a ship that the adjacency rule keeps free of other ships
+pub fn get_surroundings(board: &Board, cells: &[(usize, usize)], adjacency: Adjacency) -> Vec<(usize, usize)> {
+	let neighbours: &[(isize, isize)] = match adjacency {
+		Adjacency::Any => &[],
+		Adjacency::NoEdge => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+		Adjacency::NoEdgeOrCorner => &[(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)],
 	};
 
-	result
+	let mut surroundings = Vec::new();
+	for (pos_x, pos_y) in cells {
+		for (offset_x, offset_y) in neighbours {
+			let x = *pos_x as isize + offset_x;
+			let y = *pos_y as isize + offset_y;
+			if x < 0 || y < 0 || x >= board.size_x() as isize || y >= board.size_y() as isize {
+				continue;
+			}
+
+			let neighbour = (x as usize, y as usize);
+			if !cells.contains(&neighbour) && !surroundings.contains(&neighbour) {
+				surroundings.push(neighbour);
+			}
+		}
+	}
+
+	surroundings
+}
+
+#[test]
+fn get_surroundings_works() {
+	let board = Board::default();
+	assert_eq!(get_surroundings(&board, &[(4, 4), (5, 4)], Adjacency::Any), vec![]);
+	assert_eq!(
+		get_surroundings(&board, &[(4, 4), (5, 4)], Adjacency::NoEdge),
+		vec![(3, 4), (4, 3), (4, 5), (6, 4), (5, 3), (5, 5)]
+	);
+	assert_eq!(get_surroundings(&board, &[(4, 4), (5, 4)], Adjacency::NoEdgeOrCorner).len(), 10);
+	assert_eq!(get_surroundings(&board, &[(0, 0)], Adjacency::NoEdgeOrCorner), vec![(1, 0), (0, 1), (1, 1)]);
+}
+
+#[test]
+fn check_placement_works() {
+	let mut board = Board::new(12, 5);
+	board = place_ship(board, 4, 2, &ShipClass::new("Cruiser", 3, 1), &Rotation::Horizontal);
+	let id = board.fleet().get_at(4, 2).unwrap().id;
+	board[0][0] = Cell::Shot;
+
+	assert_eq!(check_placement(&board, 1, 0, 2, &Rotation::Horizontal, Adjacency::Any), Ok(()));
+	assert_eq!(
+		check_placement(&board, -1, 0, 2, &Rotation::Horizontal, Adjacency::Any),
+		Err(PlacementError::OutOfBounds(Edge::Left))
+	);
+	assert_eq!(
+		check_placement(&board, 1, -1, 2, &Rotation::Vertical, Adjacency::Any),
+		Err(PlacementError::OutOfBounds(Edge::Top))
+	);
+	assert_eq!(
+		check_placement(&board, 10, 0, 3, &Rotation::Horizontal, Adjacency::Any),
+		Err(PlacementError::OutOfBounds(Edge::Right))
+	);
+	assert_eq!(check_placement(&board, 11, 0, 1, &Rotation::Horizontal, Adjacency::Any), Ok(()));
+	assert_eq!(
+		check_placement(&board, 12, 0, 1, &Rotation::Horizontal, Adjacency::Any),
+		Err(PlacementError::OutOfBounds(Edge::Right))
+	);
+	assert_eq!(
+		check_placement(&board, 1, 3, 3, &Rotation::Vertical, Adjacency::Any),
+		Err(PlacementError::OutOfBounds(Edge::Bottom))
+	);
+	assert_eq!(check_placement(&board, 5, 0, 3, &Rotation::Vertical, Adjacency::Any), Err(PlacementError::Overlap(id)));
+	assert_eq!(check_placement(&board, 2, 2, 3, &Rotation::Horizontal, Adjacency::Any), Err(PlacementError::Overlap(id)));
+	assert_eq!(
+		check_placement(&board, 0, 0, 2, &Rotation::Vertical, Adjacency::Any),
+		Err(PlacementError::Occupied { pos_x: 0, pos_y: 0 })
+	);
+
+	// the cruiser lies on E3 to G3
+	let touches_edge = Err(PlacementError::Adjacent(id));
+	assert_eq!(check_placement(&board, 4, 0, 2, &Rotation::Vertical, Adjacency::NoEdge), touches_edge);
+	assert_eq!(check_placement(&board, 7, 2, 2, &Rotation::Horizontal, Adjacency::NoEdge), touches_edge);
+	assert_eq!(check_placement(&board, 8, 2, 2, &Rotation::Horizontal, Adjacency::NoEdge), Ok(()));
+	assert_eq!(check_placement(&board, 3, 0, 2, &Rotation::Vertical, Adjacency::NoEdge), Ok(()));
+	assert_eq!(check_placement(&board, 3, 0, 2, &Rotation::Vertical, Adjacency::NoEdgeOrCorner), touches_edge);
+	assert_eq!(check_placement(&board, 2, 0, 2, &Rotation::Vertical, Adjacency::NoEdgeOrCorner), Ok(()));
+	assert_eq!(check_placement(&board, 7, 3, 3, &Rotation::Horizontal, Adjacency::NoEdgeOrCorner), touches_edge);
+	assert_eq!(check_placement(&board, 8, 3, 3, &Rotation::Horizontal, Adjacency::NoEdgeOrCorner), Ok(()));
+}
+
+/// Whether a ship fits at this position without leaving the board or overlapping another ship
+pub fn is_free_space(
+	board: &Board,
+	temp_pos_x: isize,
+	temp_pos_y: isize,
+	ship_size: usize,
+	rotation: &Rotation,
+) -> bool {
+	check_placement(board, temp_pos_x, temp_pos_y, ship_size, rotation, Adjacency::Any).is_ok()
 }
 
 #[test]
 fn is_free_space_works() {
-	let mut board = [[Empty; config::SIZE_X]; config::SIZE_Y];
-	board[1][0] = Cell::Ship;
-	board[2][1] = Cell::Ship;
-	assert_eq!(is_free_space(&board, 0, 0, 2, &Rotation::Vertical), false);
-	assert_eq!(is_free_space(&board, 0, 0, 3, &Rotation::Vertical), false);
-	assert_eq!(is_free_space(&board, 1, 0, 3, &Rotation::Vertical), false);
-	assert_eq!(is_free_space(&board, 0, 2, 2, &Rotation::Horizontal), false);
-	assert_eq!(is_free_space(&board, 0, 2, 3, &Rotation::Horizontal), false);
-
-	assert_eq!(is_free_space(&board, 0, 0, 1, &Rotation::Vertical), true);
-	assert_eq!(is_free_space(&board, 1, 0, 2, &Rotation::Vertical), true);
-	assert_eq!(is_free_space(&board, 2, 0, 3, &Rotation::Vertical), true);
-	assert_eq!(is_free_space(&board, 0, 2, 1, &Rotation::Vertical), true);
-
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
+	let mut board = Board::default();
+	board[1][0] = Cell::Ship(0);
+	board[2][1] = Cell::Ship(0);
+	assert!(!is_free_space(&board, 0, 0, 2, &Rotation::Vertical));
+	assert!(!is_free_space(&board, 0, 0, 3, &Rotation::Vertical));
+	assert!(!is_free_space(&board, 1, 0, 3, &Rotation::Vertical));
+	assert!(!is_free_space(&board, 0, 2, 2, &Rotation::Horizontal));
+	assert!(!is_free_space(&board, 0, 2, 3, &Rotation::Horizontal));
+
+	assert!(is_free_space(&board, 0, 0, 1, &Rotation::Vertical));
+	assert!(is_free_space(&board, 1, 0, 2, &Rotation::Vertical));
+	assert!(is_free_space(&board, 2, 0, 3, &Rotation::Vertical));
+	assert!(is_free_space(&board, 0, 2, 1, &Rotation::Vertical));
+
+	board = Board::default();
 	for x in 0..10 {
 		for y in 0..10 {
-			assert_eq!(is_free_space(&board, x, y, 1, &Rotation::Vertical), true);
-			assert_eq!(is_free_space(&board, x, y, 1, &Rotation::Horizontal), true);
+			assert!(is_free_space(&board, x, y, 1, &Rotation::Vertical));
+			assert!(is_free_space(&board, x, y, 1, &Rotation::Horizontal));
 		}
 	}
 }
 
-pub fn get_next_available_coordinates(board: &config::Board, ship_size: usize, rotation: &Rotation) -> (usize, usize) {
-	let mut pos_x = 0;
-	let mut pos_y = 0;
+/// The free position closest to the top left corner that follows the adjacency rule, none when the ship fits nowhere
+pub fn get_next_available_coordinates(
+	board: &Board,
+	ship_size: usize,
+	rotation: &Rotation,
+	adjacency: Adjacency,
+) -> Option<(usize, usize)> {
+	let fits = |pos_x: usize, pos_y: usize| {
+		check_placement(board, pos_x as isize, pos_y as isize, ship_size, rotation, adjacency).is_ok()
+	};
 
-	'outer: for i in 0..10 {
+	for i in 0..board.size_x().max(board.size_y()) {
 		for n in 0..i {
-			if is_free_space(board, i as isize, n as isize, ship_size, rotation) {
-				pos_x = i as usize;
-				pos_y = n as usize;
-				break 'outer;
+			if fits(i, n) {
+				return Some((i, n));
 			}
 
-			if is_free_space(board, n as isize, i as isize, ship_size, rotation) {
-				pos_x = n as usize;
-				pos_y = i as usize;
-				break 'outer;
+			if fits(n, i) {
+				return Some((n, i));
 			}
 		}
 
-		if is_free_space(board, i as isize, i as isize, ship_size, rotation) {
-			pos_x = i as usize;
-			pos_y = i as usize;
-			break;
+		if fits(i, i) {
+			return Some((i, i));
 		}
 	}
 
-	(pos_x, pos_y)
+	None
 }
 
 #[test]
 fn get_next_available_coordinates_works() {
-	let mut board = [[Empty; config::SIZE_X]; config::SIZE_Y];
-	assert_eq!(get_next_available_coordinates(&board, 1, &Rotation::Vertical), (0, 0));
+	let mut board = Board::default();
+	assert_eq!(get_next_available_coordinates(&board, 1, &Rotation::Vertical, Adjacency::Any), Some((0, 0)));
 
-	board[0][0] = Cell::Ship;
-	assert_eq!(get_next_available_coordinates(&board, 1, &Rotation::Vertical), (1, 0));
+	board[0][0] = Cell::Ship(0);
+	assert_eq!(get_next_available_coordinates(&board, 1, &Rotation::Vertical, Adjacency::Any), Some((1, 0)));
 
-	board[0][1] = Cell::Ship;
-	assert_eq!(get_next_available_coordinates(&board, 1, &Rotation::Vertical), (0, 1));
+	board[0][1] = Cell::Ship(0);
+	assert_eq!(get_next_available_coordinates(&board, 1, &Rotation::Vertical, Adjacency::Any), Some((0, 1)));
 
-	board[1][0] = Cell::Ship;
-	assert_eq!(get_next_available_coordinates(&board, 1, &Rotation::Vertical), (1, 1));
+	board[1][0] = Cell::Ship(0);
+	assert_eq!(get_next_available_coordinates(&board, 1, &Rotation::Vertical, Adjacency::Any), Some((1, 1)));
 
-	board = [[Empty; config::SIZE_X]; config::SIZE_Y];
-	board[1][0] = Cell::Ship;
-	assert_eq!(get_next_available_coordinates(&board, 2, &Rotation::Vertical), (1, 0));
+	board = Board::default();
+	board[1][0] = Cell::Ship(0);
+	assert_eq!(get_next_available_coordinates(&board, 2, &Rotation::Vertical, Adjacency::Any), Some((1, 0)));
+
+	board = Board::new(12, 4);
+	for x in 0..10 {
+		for y in 0..4 {
+			board[y][x] = Cell::Ship(0);
+		}
+	}
+	assert_eq!(get_next_available_coordinates(&board, 2, &Rotation::Horizontal, Adjacency::Any), Some((10, 0)));
+
+	board = place_ship(Board::default(), 0, 0, &ShipClass::new("Destroyer", 2, 1), &Rotation::Horizontal);
+	assert_eq!(get_next_available_coordinates(&board, 2, &Rotation::Horizontal, Adjacency::Any), Some((0, 1)));
+	assert_eq!(get_next_available_coordinates(&board, 2, &Rotation::Horizontal, Adjacency::NoEdge), Some((0, 2)));
+
+	board = place_ship(Board::default(), 1, 1, &ShipClass::new("Patrol boat", 1, 1), &Rotation::Horizontal);
+	assert_eq!(get_next_available_coordinates(&board, 1, &Rotation::Horizontal, Adjacency::NoEdge), Some((0, 0)));
+	assert_eq!(get_next_available_coordinates(&board, 1, &Rotation::Horizontal, Adjacency::NoEdgeOrCorner), Some((3, 0)));
+
+	board = Board::new(9, 4);
+	assert_eq!(get_next_available_coordinates(&board, 5, &Rotation::Vertical, Adjacency::Any), None);
+	assert_eq!(get_next_available_coordinates(&board, 9, &Rotation::Horizontal, Adjacency::Any), Some((0, 0)));
+	board[3][4] = Cell::Ship(0);
+	board[1][0] = Cell::Ship(1);
+	board[0][4] = Cell::Ship(1);
+	board[2][5] = Cell::Ship(1);
+	assert_eq!(get_next_available_coordinates(&board, 9, &Rotation::Horizontal, Adjacency::Any), None);
 }
@@ -0,0 +1,426 @@
+//! A plain-text format for boards to write readable test fixtures and paste positions into bug reports
+//!
+//! Every row of the board is one line of symbols without spaces, top row first:
+//!
+//! | Symbol    | Cell                                                      |
+//! |-----------|-----------------------------------------------------------|
+//! | `.`       | water or a cell we know nothing about                     |
+//! | `o`       | a shot that missed                                        |
+//! | `x`       | a hit on a ship we can't see                              |
+//! | `A` - `Z` | an intact part of a placed ship, except `O` and `X`       |
+//! | `a` - `z` | a part of a placed ship that shows a hit                 |
+//! | `#`       | the placeholder of the ship we are placing                |
+//! | `+`       | the crosshair                                             |
+//!
+//! Ships are lettered in the order they were placed, so a board can hold up to 24 of them. After the rows an
+//! optional legend names the class of a ship, a ship without a name is named after its letter:
+//!
+//! ```text
+//! AAa..
+//! ...o.
+//! .x..B
+//! ....B
+//!
+//! A Cruiser
+//! B Destroyer amount=2 id=3 damage=x.
+//! next=5
+//! ```
+//!
+//! A name can be followed by what the rows can't show: `amount=` how many ships the class has in the fleet (default
+//! 1), `id=` the id of the ship (default the one after the ship before it) and `damage=` which of its cells were hit
+//! from its start to its end. The last one is for hidden boards, where a hit leaves the cell of a ship as it was. A
+//! line `next=` at the end holds the id the next ship gets when ships were picked up after the last one.
+
+use crate::board::Board;
+use crate::ships::{ShipClass, ShipId};
+use crate::Cell;
+
+/// Why a text can't be read as a board
+#[derive(Clone, Debug, PartialEq)]
+pub enum NotationError {
+	/// The text has no rows
+	Empty,
+	/// This line is not as long as the first row
+	UnevenRow { line: usize },
+	/// This line holds a symbol that is not part of the format
+	UnknownSymbol { line: usize, symbol: char },
+	/// The cells of the ship with this letter are not one straight unbroken line
+	BrokenShip(char),
+	/// This line of the legend is not a letter of a ship on the board followed by a name and its details
+	Legend { line: usize },
+}
+
+// what the legend says about the ship with this letter
+struct Entry {
+	letter: char,
+	name: String,
+	amount: usize,
+	id: Option<ShipId>,
+	damage: Option<Vec<bool>>,
+	line: usize,
+}
+
+// o and x already stand for a miss and a hit
+const LETTERS: &str = "ABCDEFGHIJKLMNPQRSTUVWYZ";
+
+/// Read a board from the text format
+pub fn parse(text: &str) -> Result<Board, NotationError> {
+	let mut rows: Vec<Vec<char>> = Vec::new();
+	let mut entries: Vec<Entry> = Vec::new();
+	let mut next_id: Option<ShipId> = None;
+
+	for (index, line) in text.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() {
+			continue;
+		}
+
+		// rows have no spaces so every line with one is part of the legend
+		if let Some((letter, rest)) = line.split_once(' ') {
+			let entry = parse_entry(letter, rest, index + 1).ok_or(NotationError::Legend { line: index + 1 })?;
+			entries.push(entry);
+			continue;
+		}
+
+		if let Some(value) = line.strip_prefix("next=") {
+			next_id = Some(value.parse().map_err(|_| NotationError::Legend { line: index + 1 })?);
+			continue;
+		}
+		if !entries.is_empty() || next_id.is_some() {
+			return Err(NotationError::Legend { line: index + 1 });
+		}
+		if let Some(symbol) =
+			line.chars().find(|symbol| !".ox#+".contains(*symbol) && !LETTERS.contains(symbol.to_ascii_uppercase()))
+		{
+			return Err(NotationError::UnknownSymbol {
+				line: index + 1,
+				symbol,
+			});
+		}
+		if rows.first().is_some_and(|first| first.len() != line.chars().count()) {
+			return Err(NotationError::UnevenRow { line: index + 1 });
+		}
+
+		rows.push(line.chars().collect());
+	}
+
+	if rows.is_empty() {
+		return Err(NotationError::Empty);
+	}
+
+	let mut board = Board::new(rows[0].len(), rows.len());
+	for (pos_y, row) in rows.iter().enumerate() {
+		for (pos_x, symbol) in row.iter().enumerate() {
+			board[pos_y][pos_x] = match symbol {
+				'o' => Cell::Shot,
+				'x' => Cell::Damage,
+				'#' => Cell::Placeholder,
+				'+' => Cell::Crosshair,
+				_ => Cell::Empty,
+			};
+		}
+	}
+
+	for letter in LETTERS.chars() {
+		let cells = rows
+			.iter()
+			.enumerate()
+			.flat_map(|(pos_y, row)| row.iter().enumerate().map(move |(pos_x, symbol)| (pos_x, pos_y, *symbol)))
+			.filter(|(_, _, symbol)| symbol.to_ascii_uppercase() == letter)
+			.collect::<Vec<(usize, usize, char)>>();
+		if cells.is_empty() {
+			continue;
+		}
+
+		// cells are collected row by row so a ship reads from its start to its end like movement::place_ship lays it
+		let (start_x, start_y, _) = cells[0];
+		let is_line = cells.iter().enumerate().all(|(index, (pos_x, pos_y, _))| {
+			(*pos_x, *pos_y) == (start_x + index, start_y) || (*pos_x, *pos_y) == (start_x, start_y + index)
+		});
+		let is_straight =
+			cells.iter().all(|(_, pos_y, _)| *pos_y == start_y) || cells.iter().all(|(pos_x, _, _)| *pos_x == start_x);
+		if !is_line || !is_straight {
+			return Err(NotationError::BrokenShip(letter));
+		}
+
+		let entry = entries.iter().find(|entry| entry.letter == letter);
+		let name = entry.map_or(letter.to_string(), |entry| entry.name.clone());
+		let class = ShipClass::new(&name, cells.len(), entry.map_or(1, |entry| entry.amount));
+		let positions: Vec<(usize, usize)> = cells.iter().map(|(pos_x, pos_y, _)| (*pos_x, *pos_y)).collect();
+		let id = match entry.and_then(|entry| entry.id.map(|id| (id, entry.line))) {
+			Some((id, line)) => board.fleet_mut().add_with_id(id, &class, positions).ok_or(NotationError::Legend { line })?,
+			None => board.fleet_mut().add(&class, positions),
+		};
+
+		let damage = entry.and_then(|entry| entry.damage.as_ref().map(|damage| (damage, entry.line)));
+		if let Some((damage, line)) = damage {
+			if damage.len() != cells.len() {
+				return Err(NotationError::Legend { line });
+			}
+			for ((pos_x, pos_y, _), _) in cells.iter().zip(damage).filter(|(_, is_hit)| **is_hit) {
+				board.fleet_mut().record_hit(*pos_x, *pos_y);
+			}
+		}
+
+		for (pos_x, pos_y, symbol) in cells {
+			board[pos_y][pos_x] = if symbol.is_ascii_lowercase() {
+				board.fleet_mut().record_hit(pos_x, pos_y);
+				Cell::Damage
+			} else {
+				Cell::Ship(id)
+			};
+		}
+	}
+
+	// every name in the legend has to belong to a ship on the board
+	let unknown =
+		entries.iter().find(|entry| !rows.iter().flatten().any(|symbol| symbol.to_ascii_uppercase() == entry.letter));
+	if let Some(entry) = unknown {
+		return Err(NotationError::Legend { line: entry.line });
+	}
+	if let Some(next_id) = next_id {
+		board.fleet_mut().skip_ids(next_id);
+	}
+
+	Ok(board)
+}
+
+// a letter followed by a name and what the rows can't show about the ship, none when the line can't be read
+fn parse_entry(letter: &str, rest: &str, line: usize) -> Option<Entry> {
+	let mut letters = letter.chars();
+	let letter = match (letters.next(), letters.next()) {
+		(Some(letter), None) if LETTERS.contains(letter) => letter,
+		_ => return None,
+	};
+
+	let mut entry = Entry {
+		letter,
+		name: String::new(),
+		amount: 1,
+		id: None,
+		damage: None,
+		line,
+	};
+
+	// names may have spaces so the details are taken off the end
+	let mut words: Vec<&str> = rest.split_whitespace().collect();
+	while let Some((key, value)) = words.last().and_then(|word| word.split_once('=')) {
+		match key {
+			"amount" => entry.amount = value.parse().ok().filter(|amount| *amount > 0)?,
+			"id" => entry.id = Some(value.parse().ok()?),
+			"damage" => {
+				let damage = value.chars().map(|symbol| match symbol {
+					'x' => Some(true),
+					'.' => Some(false),
+					_ => None,
+				});
+				entry.damage = Some(damage.collect::<Option<Vec<bool>>>()?);
+			}
+			_ => return None,
+		}
+		words.pop();
+	}
+
+	entry.name = words.join(" ");
+	if entry.name.is_empty() {
+		return None;
+	}
+
+	Some(entry)
+}
+
+/// Write a board in the text format, boards with more than 24 ships can't be read back
+pub fn format(board: &Board) -> String {
+	let ships = board.fleet().get_ships();
+	let mut output = String::new();
+
+	for (pos_y, row) in board.rows().enumerate() {
+		for (pos_x, cell) in row.iter().enumerate() {
+			let ship = match cell {
+				Cell::Ship(id) => board.fleet().get(*id),
+				Cell::Damage => board.fleet().get_at(pos_x, pos_y),
+				_ => None,
+			};
+
+			output.push(match (cell, ship) {
+				(Cell::Ship(_), Some(ship)) | (Cell::Damage, Some(ship)) => {
+					let index = ships.iter().position(|item| item.id == ship.id).unwrap_or(0);
+					let letter = LETTERS.chars().nth(index).unwrap_or('?');
+
+					// a hit on a hidden board leaves the cell as it was, the legend tells about it instead
+					if *cell == Cell::Damage {
+						letter.to_ascii_lowercase()
+					} else {
+						letter
+					}
+				}
+				(Cell::Ship(_), None) => '?',
+				(Cell::Empty, _) => '.',
+				(Cell::Shot, _) => 'o',
+				(Cell::Damage, None) => 'x',
+				(Cell::Placeholder, _) => '#',
+				(Cell::Crosshair, _) => '+',
+			});
+		}
+		output.push('\n');
+	}
+
+	if !ships.is_empty() {
+		output.push('\n');
+	}
+
+	// the details are only written when they differ from what the rows and the order of the ships tell
+	let mut next_id = 0;
+	for (ship, letter) in ships.iter().zip(LETTERS.chars()) {
+		output += &format!("{} {}", letter, ship.class.name);
+		if ship.class.amount != 1 {
+			output += &format!(" amount={}", ship.class.amount);
+		}
+		if ship.id != next_id {
+			output += &format!(" id={}", ship.id);
+		}
+		let is_hidden = ship
+			.cells
+			.iter()
+			.zip(&ship.damage)
+			.any(|((pos_x, pos_y), damage)| *damage && board[*pos_y][*pos_x] != Cell::Damage);
+		if is_hidden {
+			let damage: String = ship.damage.iter().map(|damage| if *damage { 'x' } else { '.' }).collect();
+			output += &format!(" damage={}", damage);
+		}
+		output.push('\n');
+		next_id = ship.id + 1;
+	}
+
+	if board.fleet().next_id() != next_id {
+		output += &format!("next={}\n", board.fleet().next_id());
+	}
+
+	output
+}
+
+#[cfg(test)]
+use crate::{
+	engine::{Command, Game, Outcome, Rules},
+	movement, Rotation,
+};
+
+#[test]
+fn parse_works() {
+	let board = parse(
+		"
+		AAa..
+		...o.
+		.x#+B
+		....B
+
+		A Cruiser
+		",
+	)
+	.unwrap();
+
+	assert_eq!((board.size_x(), board.size_y()), (5, 4));
+	assert_eq!(board[0][0], Cell::Ship(0));
+	assert_eq!(board[0][2], Cell::Damage);
+	assert_eq!(board[1][3], Cell::Shot);
+	assert_eq!(board[2][1], Cell::Damage);
+	assert_eq!(board[2][2], Cell::Placeholder);
+	assert_eq!(board[2][3], Cell::Crosshair);
+	assert_eq!(board[3][4], Cell::Ship(1));
+
+	let ships = board.fleet().get_ships();
+	assert_eq!(ships[0].class, ShipClass::new("Cruiser", 3, 1));
+	assert_eq!(ships[0].damage, vec![false, false, true]);
+	assert_eq!(ships[1].class, ShipClass::new("B", 2, 1));
+	assert_eq!(ships[1].cells, vec![(4, 2), (4, 3)]);
+	assert!(board.fleet().get_at(1, 2).is_none());
+}
+
+#[test]
+fn parse_fails_on_broken_boards() {
+	assert_eq!(parse(""), Err(NotationError::Empty));
+	assert_eq!(parse("...\n..\n"), Err(NotationError::UnevenRow { line: 2 }));
+	assert_eq!(parse("..\n.*\n"), Err(NotationError::UnknownSymbol { line: 2, symbol: '*' }));
+	assert_eq!(parse("..\nX.\n"), Err(NotationError::UnknownSymbol { line: 2, symbol: 'X' }));
+	assert_eq!(parse("A.A\n"), Err(NotationError::BrokenShip('A')));
+	assert_eq!(parse("A.\n.A\n"), Err(NotationError::BrokenShip('A')));
+	assert_eq!(parse("AA\n\nA Cruiser\n.."), Err(NotationError::Legend { line: 4 }));
+	assert_eq!(parse("AA\n\nB Cruiser\n"), Err(NotationError::Legend { line: 3 }));
+	assert_eq!(parse("AA\n\nCruiser A\n"), Err(NotationError::Legend { line: 3 }));
+}
+
+#[test]
+fn format_works() {
+	let cruiser = ShipClass::new("Cruiser", 3, 1);
+	let mut board = movement::place_ship(Board::new(6, 3), 0, 0, &cruiser, &Rotation::Vertical);
+	board = movement::place_ship(board, 2, 2, &ShipClass::new("Destroyer", 2, 1), &Rotation::Horizontal);
+	board.fleet_mut().record_hit(0, 1);
+	board[1][0] = Cell::Damage;
+	board[0][5] = Cell::Shot;
+	board[1][4] = Cell::Damage;
+	board[0][3] = Cell::Crosshair;
+
+	let text = format(&board);
+	assert_eq!(text, "A..+.o\na...x.\nA.BB..\n\nA Cruiser\nB Destroyer\n");
+	assert_eq!(parse(&text).unwrap(), board);
+
+	// an observed board without ships round-trips as well
+	let observed = parse("o..x\n..+.\n").unwrap();
+	assert_eq!(format(&observed), "o..x\n..+.\n");
+}
+
+#[test]
+fn legend_details_work() {
+	let board = parse("AA.B\n...B\n\nA Patrol boat amount=2 id=3 damage=.x\nB Destroyer\nnext=7\n").unwrap();
+	let ships = board.fleet().get_ships();
+	assert_eq!(ships[0].class, ShipClass::new("Patrol boat", 2, 2));
+	assert_eq!((ships[0].id, ships[1].id), (3, 4));
+	assert_eq!(board[0][1], Cell::Ship(3));
+	assert_eq!(ships[0].damage, vec![false, true]);
+	assert_eq!(board.fleet().next_id(), 7);
+	assert_eq!(parse(&format(&board)).unwrap(), board);
+
+	assert_eq!(parse("AB\n\nB Cruiser id=0\n"), Err(NotationError::Legend { line: 3 }));
+	assert_eq!(parse("AA\n\nA Cruiser damage=x\n"), Err(NotationError::Legend { line: 3 }));
+	assert_eq!(parse("AA\n\nA Cruiser amount=0\n"), Err(NotationError::Legend { line: 3 }));
+	assert_eq!(parse("AA\n\nA Cruiser size=2\n"), Err(NotationError::Legend { line: 3 }));
+	assert_eq!(parse("AA\n\nA amount=2\n"), Err(NotationError::Legend { line: 3 }));
+	assert_eq!(parse("AA\nnext=2\n.."), Err(NotationError::Legend { line: 3 }));
+}
+
+#[test]
+fn boards_of_a_game_round_trip() {
+	let mut game = Game::new(Rules {
+		seed: Some(7),
+		..Rules::default()
+	})
+	.unwrap();
+	assert_eq!(game.execute(Command::Randomise), Ok(Outcome::FleetRandomised));
+
+	// picking up the first ship leaves a gap in the ids and its placeholder on the board
+	let first = game.board_me().fleet().get_ships()[0].cells[0];
+	assert!(matches!(
+		game.execute(Command::PickUp {
+			pos_x: first.0,
+			pos_y: first.1
+		}),
+		Ok(Outcome::PickedUp(_))
+	));
+	assert_eq!(parse(&format(game.board_me())).unwrap(), *game.board_me());
+	assert!(matches!(game.execute(Command::PlaceShip), Ok(Outcome::FleetPlaced(_))));
+	assert!(matches!(game.execute(Command::Undo), Ok(Outcome::PickedUp(_))));
+	assert!(format(game.board_me()).contains("\nnext="));
+	assert_eq!(parse(&format(game.board_me())).unwrap(), *game.board_me());
+	assert!(matches!(game.execute(Command::PlaceShip), Ok(Outcome::FleetPlaced(_))));
+	assert_eq!(game.execute(Command::Confirm), Ok(Outcome::BattleStarted));
+
+	// hits on the hidden board keep the cells of the ships and only show in the legend
+	let targets: Vec<(usize, usize)> = game.board_secret().fleet().get_ships().iter().map(|ship| ship.cells[0]).collect();
+	for (pos_x, pos_y) in targets {
+		assert!(matches!(game.execute(Command::Fire { pos_x, pos_y }), Ok(Outcome::Shot(_))));
+	}
+	let text = format(game.board_secret());
+	assert!(text.contains(" damage=x."));
+	assert_eq!(parse(&text).unwrap(), *game.board_secret());
+	assert_eq!(parse(&format(game.board_ai())).unwrap(), *game.board_ai());
+}
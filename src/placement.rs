@@ -0,0 +1,197 @@
+//! How the AI arranges its fleet, chosen apart from how well it shoots
+//!
+//! Every personality but the random one draws [`config::PLACEMENT_LAYOUTS`] random layouts with [`ai::set_ships`]
+//! and keeps the one that suits it best, so it still follows the adjacency rule and doesn't repeat itself.
+
+use crate::ai::{self, FleetError, Observation};
+use crate::board::Board;
+use crate::config;
+use crate::density::Density;
+use crate::engine::Rules;
+use crate::ships::PlacedShip;
+use crate::GameRng;
+
+#[cfg(test)]
+use crate::{
+	movement::{self, Adjacency},
+	ships::ShipClass,
+	Rotation,
+};
+#[cfg(test)]
+use rand::SeedableRng;
+
+/// Where the AI likes to put its ships
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Placement {
+	/// Every legal layout is about equally likely
+	#[default]
+	Random,
+	/// Ships hug the edges of the board
+	Edges,
+	/// Ships keep away from the centre and end up in the corners
+	NoCentre,
+	/// Ships keep as far from their nearest neighbour as they can
+	Spread,
+	/// Ships huddle together
+	Cluster,
+	/// Ships sit on the cells a hunter that shoots where most placements meet checks last
+	AntiDensity,
+}
+
+impl Placement {
+	/// Every placement, the random one first
+	pub const ALL: [Placement; 6] = [
+		Placement::Random,
+		Placement::Edges,
+		Placement::NoCentre,
+		Placement::Spread,
+		Placement::Cluster,
+		Placement::AntiDensity,
+	];
+
+	/// The name a placement goes by on the command line
+	pub fn name(&self) -> &'static str {
+		match self {
+			Placement::Random => "random",
+			Placement::Edges => "edges",
+			Placement::NoCentre => "no-centre",
+			Placement::Spread => "spread",
+			Placement::Cluster => "cluster",
+			Placement::AntiDensity => "anti-density",
+		}
+	}
+
+	/// How badly a layout suits the placement, the lower the better
+	pub fn get_score(&self, board: &Board, rules: &Rules) -> i64 {
+		let ships = board.fleet().get_ships();
+		let cells = ships.iter().flat_map(|ship| ship.cells.iter().copied());
+		let (size_x, size_y) = (board.size_x() as i64, board.size_y() as i64);
+
+		match self {
+			Placement::Random => 0,
+			Placement::Edges => cells
+				.map(|(pos_x, pos_y)| {
+					let (pos_x, pos_y) = (pos_x as i64, pos_y as i64);
+					pos_x.min(pos_y).min(size_x - 1 - pos_x).min(size_y - 1 - pos_y)
+				})
+				.sum(),
+			// in half cells so the centre of a board with an even side stays a whole number
+			Placement::NoCentre => -cells
+				.map(|(pos_x, pos_y)| (2 * pos_x as i64 + 1 - size_x).pow(2) + (2 * pos_y as i64 + 1 - size_y).pow(2))
+				.sum::<i64>(),
+			Placement::Spread => -(0..ships.len())
+				.map(|index| {
+					let others = ships.iter().enumerate().filter(|(other, _)| *other != index);
+					others.map(|(_, other)| get_gap(&ships[index], other)).min().unwrap_or(0)
+				})
+				.sum::<i64>(),
+			Placement::Cluster => (0..ships.len())
+				.flat_map(|index| ships[index + 1..].iter().map(move |other| (index, other)))
+				.map(|(index, other)| get_gap(&ships[index], other))
+				.sum(),
+			Placement::AntiDensity => {
+				let empty = Board::new(board.size_x(), board.size_y());
+				let density = Density::new(&Observation::new(&empty, &empty, rules));
+				cells.map(|(pos_x, pos_y)| density.get(pos_x, pos_y) as i64).sum()
+			}
+		}
+	}
+}
+
+// how many steps a king would need from the closest cell of one ship to the other
+fn get_gap(ship: &PlacedShip, other: &PlacedShip) -> i64 {
+	ship
+		.cells
+		.iter()
+		.flat_map(|(pos_x, pos_y)| {
+			other.cells.iter().map(move |(other_x, other_y)| pos_x.abs_diff(*other_x).max(pos_y.abs_diff(*other_y)))
+		})
+		.min()
+		.unwrap_or(0) as i64
+}
+
+/// Place the AI fleet on an empty board the way the placement in the rules likes it
+pub fn place_fleet(rules: &Rules, rng: &mut GameRng) -> Result<Board, FleetError> {
+	let get_layout =
+		|rng: &mut GameRng| ai::set_ships(Board::new(rules.size_x, rules.size_y), &rules.fleet, rules.adjacency, rng);
+
+	let mut best = get_layout(rng)?;
+	if rules.placement == Placement::Random {
+		return Ok(best);
+	}
+
+	let mut best_score = rules.placement.get_score(&best, rules);
+	for _ in 1..config::PLACEMENT_LAYOUTS {
+		let board = get_layout(rng)?;
+		let score = rules.placement.get_score(&board, rules);
+		if score < best_score {
+			best = board;
+			best_score = score;
+		}
+	}
+
+	Ok(best)
+}
+
+#[test]
+fn random_placement_works() {
+	let rules = Rules::default();
+	let board = place_fleet(&rules, &mut GameRng::seed_from_u64(3)).unwrap();
+	let plain = ai::set_ships(Board::default(), &rules.fleet, rules.adjacency, &mut GameRng::seed_from_u64(3));
+	assert_eq!(Ok(board), plain);
+}
+
+#[test]
+fn placements_beat_random_layouts() {
+	for placement in Placement::ALL.into_iter().skip(1) {
+		let rules = Rules {
+			fleet: config::get_classic_fleet(),
+			adjacency: Adjacency::NoEdge,
+			placement,
+			..Rules::default()
+		};
+
+		let mut better = 0;
+		for seed in 0..10 {
+			let board = place_fleet(&rules, &mut GameRng::seed_from_u64(seed)).unwrap();
+			assert_eq!(board.fleet().get_ships().len(), 5);
+
+			let plain = ai::set_ships(Board::default(), &rules.fleet, rules.adjacency, &mut GameRng::seed_from_u64(seed));
+			let plain_score = placement.get_score(&plain.unwrap(), &rules);
+			assert!(placement.get_score(&board, &rules) <= plain_score, "{}", placement.name());
+			if placement.get_score(&board, &rules) < plain_score {
+				better += 1;
+			}
+		}
+		assert!(better > 5, "{}", placement.name());
+	}
+}
+
+#[test]
+fn get_score_works() {
+	let rules = Rules {
+		size_x: 4,
+		size_y: 4,
+		fleet: vec![ShipClass::new("Destroyer", 2, 2)],
+		..Rules::default()
+	};
+	let get_board = |cells: [(usize, usize); 2]| {
+		let mut board = Board::new(4, 4);
+		for (pos_x, pos_y) in cells {
+			board = movement::place_ship(board, pos_x, pos_y, &rules.fleet[0], &Rotation::Horizontal);
+		}
+		board
+	};
+	let corners = get_board([(0, 0), (2, 3)]);
+	let middle = get_board([(1, 1), (1, 2)]);
+
+	assert_eq!(Placement::Random.get_score(&middle, &rules), 0);
+	assert_eq!(Placement::Edges.get_score(&corners, &rules), 0);
+	assert_eq!(Placement::Edges.get_score(&middle, &rules), 4);
+	assert!(Placement::NoCentre.get_score(&corners, &rules) < Placement::NoCentre.get_score(&middle, &rules));
+	assert_eq!(Placement::Spread.get_score(&corners, &rules), -6);
+	assert_eq!(Placement::Spread.get_score(&middle, &rules), -2);
+	assert_eq!(Placement::Cluster.get_score(&middle, &rules), 1);
+	// fewer placements of a destroyer cover a corner than a cell in the middle
+	assert!(Placement::AntiDensity.get_score(&corners, &rules) < Placement::AntiDensity.get_score(&middle, &rules));
+}
@@ -0,0 +1,300 @@
+//! Named fleet layouts that can be saved to and loaded from a local file
+//!
+//! A file holds any number of presets, each one starts with its name in brackets followed by the board size and one
+//! line per ship with its position, rotation and class:
+//!
+//! ```text
+//! [Corner camp]
+//! size 10x10
+//! 0 0 horizontal Destroyer
+//! 9 7 vertical Patrol boat
+//! ```
+
+use crate::board::Board;
+use crate::engine::Rules;
+use crate::movement::{self, PlacementError};
+use crate::ships::ShipTracker;
+use crate::Rotation;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A ship within a preset, `class` refers to a ship class of the fleet by its name
+#[derive(Clone, Debug, PartialEq)]
+pub struct PresetShip {
+	pub class: String,
+	pub pos_x: usize,
+	pub pos_y: usize,
+	pub rotation: Rotation,
+}
+
+/// A named layout of a whole fleet
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preset {
+	pub name: String,
+	pub size_x: usize,
+	pub size_y: usize,
+	pub ships: Vec<PresetShip>,
+}
+
+/// Why a preset can't be read or used
+#[derive(Clone, Debug, PartialEq)]
+pub enum PresetError {
+	/// This line of the file could not be read
+	Syntax { line: usize },
+	/// The preset was made for a board of another size
+	WrongSize { size_x: usize, size_y: usize },
+	/// The preset has a ship that is not part of the fleet or more of them than the fleet has
+	UnknownShip(String),
+	/// The preset is missing ships of the fleet
+	MissingShips,
+	/// The ship of this class can't be placed where the preset puts it
+	InvalidPlacement(String, PlacementError),
+	/// The file could not be read or written
+	File(String),
+}
+
+impl Preset {
+	/// A preset of all ships placed on a board
+	pub fn from_board(name: &str, board: &Board) -> Self {
+		let ships = board
+			.fleet()
+			.get_ships()
+			.iter()
+			.map(|ship| {
+				let (pos_x, pos_y) = ship.cells[0];
+				let rotation = match ship.cells.get(1) {
+					Some((next_x, _)) if *next_x == pos_x => Rotation::Vertical,
+					_ => Rotation::Horizontal,
+				};
+
+				PresetShip {
+					class: ship.class.name.clone(),
+					pos_x,
+					pos_y,
+					rotation,
+				}
+			})
+			.collect();
+
+		Self {
+			name: String::from(name),
+			size_x: board.size_x(),
+			size_y: board.size_y(),
+			ships,
+		}
+	}
+
+	/// Place the preset on an empty board after checking it against the size, fleet and adjacency rule of a game
+	pub fn to_board(&self, rules: &Rules) -> Result<Board, PresetError> {
+		if (self.size_x, self.size_y) != (rules.size_x, rules.size_y) {
+			return Err(PresetError::WrongSize {
+				size_x: self.size_x,
+				size_y: self.size_y,
+			});
+		}
+
+		let mut tracker = ShipTracker::new(&rules.fleet);
+		let mut board = Board::new(rules.size_x, rules.size_y);
+		for ship in &self.ships {
+			let index = rules.fleet.iter().position(|kind| kind.name == ship.class);
+			let kind = match index {
+				Some(index) if tracker.get_ships()[index] > 0 => &rules.fleet[index],
+				_ => return Err(PresetError::UnknownShip(ship.class.clone())),
+			};
+
+			movement::check_placement(
+				&board,
+				ship.pos_x as isize,
+				ship.pos_y as isize,
+				kind.size,
+				&ship.rotation,
+				rules.adjacency,
+			)
+			.map_err(|error| PresetError::InvalidPlacement(ship.class.clone(), error))?;
+			board = movement::place_ship(board, ship.pos_x, ship.pos_y, kind, &ship.rotation);
+			tracker.set_ship(kind);
+		}
+
+		if tracker.get_remaining() > 0 {
+			return Err(PresetError::MissingShips);
+		}
+
+		Ok(board)
+	}
+}
+
+/// Read all presets from the text format
+pub fn parse(text: &str) -> Result<Vec<Preset>, PresetError> {
+	let mut presets: Vec<Preset> = Vec::new();
+
+	for (index, line) in text.lines().enumerate() {
+		let line = line.trim();
+		let syntax = PresetError::Syntax { line: index + 1 };
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+
+		if line.starts_with('[') && line.ends_with(']') {
+			presets.push(Preset {
+				name: String::from(line[1..line.len() - 1].trim()),
+				size_x: 0,
+				size_y: 0,
+				ships: Vec::new(),
+			});
+			continue;
+		}
+
+		let preset = presets.last_mut().ok_or_else(|| syntax.clone())?;
+		let mut words = line.splitn(4, ' ');
+		match (words.next(), words.next(), words.next(), words.next()) {
+			(Some("size"), Some(size), None, None) => {
+				let (size_x, size_y) = size.split_once('x').ok_or_else(|| syntax.clone())?;
+				preset.size_x = size_x.parse().map_err(|_| syntax.clone())?;
+				preset.size_y = size_y.parse().map_err(|_| syntax.clone())?;
+			}
+			(Some(pos_x), Some(pos_y), Some(rotation), Some(class)) => preset.ships.push(PresetShip {
+				class: String::from(class.trim()),
+				pos_x: pos_x.parse().map_err(|_| syntax.clone())?,
+				pos_y: pos_y.parse().map_err(|_| syntax.clone())?,
+				rotation: match rotation {
+					"horizontal" => Rotation::Horizontal,
+					"vertical" => Rotation::Vertical,
+					_ => return Err(syntax),
+				},
+			}),
+			_ => return Err(syntax),
+		}
+	}
+
+	Ok(presets)
+}
+
+/// Write presets in the text format
+pub fn format(presets: &[Preset]) -> String {
+	let mut output = String::new();
+
+	for preset in presets {
+		output += &format!("[{}]\nsize {}x{}\n", preset.name, preset.size_x, preset.size_y);
+		for ship in &preset.ships {
+			let rotation = match ship.rotation {
+				Rotation::Horizontal => "horizontal",
+				Rotation::Vertical => "vertical",
+			};
+			output += &format!("{} {} {} {}\n", ship.pos_x, ship.pos_y, rotation, ship.class);
+		}
+		output += "\n";
+	}
+
+	output
+}
+
+/// Read all presets from a file, a file that does not exist yet holds no presets
+pub fn load(path: &Path) -> Result<Vec<Preset>, PresetError> {
+	match fs::read_to_string(path) {
+		Ok(text) => parse(&text),
+		Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+		Err(error) => Err(PresetError::File(error.to_string())),
+	}
+}
+
+/// Add a preset to a file, replacing any preset of the same name
+pub fn save(path: &Path, preset: Preset) -> Result<(), PresetError> {
+	let mut presets = load(path)?;
+	presets.retain(|item| item.name != preset.name);
+	presets.push(preset);
+
+	fs::write(path, format(&presets)).map_err(|error| PresetError::File(error.to_string()))
+}
+
+#[cfg(test)]
+use crate::{movement::Adjacency, ships::ShipClass};
+
+#[cfg(test)]
+fn get_rules() -> Rules {
+	Rules {
+		fleet: vec![ShipClass::new("Destroyer", 2, 1), ShipClass::new("Patrol boat", 1, 2)],
+		adjacency: Adjacency::NoEdge,
+		..Rules::default()
+	}
+}
+
+#[test]
+fn parse_and_format_work() {
+	let text = "# my layouts\n[Corner camp]\nsize 10x10\n0 0 horizontal Destroyer\n9 7 vertical Patrol boat\n\n[Empty]\nsize 9x4\n";
+	let presets = parse(text).unwrap();
+	assert_eq!(presets.len(), 2);
+	assert_eq!(presets[0].name, "Corner camp");
+	assert_eq!((presets[0].size_x, presets[0].size_y), (10, 10));
+	assert_eq!(
+		presets[0].ships[1],
+		PresetShip {
+			class: String::from("Patrol boat"),
+			pos_x: 9,
+			pos_y: 7,
+			rotation: Rotation::Vertical,
+		}
+	);
+	assert!(presets[1].ships.is_empty());
+	assert_eq!(parse(&format(&presets)).unwrap(), presets);
+
+	assert_eq!(parse("0 0 horizontal Destroyer"), Err(PresetError::Syntax { line: 1 }));
+	assert_eq!(parse("[A]\nsize ten"), Err(PresetError::Syntax { line: 2 }));
+	assert_eq!(parse("[A]\n0 0 diagonal Destroyer"), Err(PresetError::Syntax { line: 2 }));
+	assert_eq!(parse("[A]\n0 zero horizontal Destroyer"), Err(PresetError::Syntax { line: 2 }));
+}
+
+#[test]
+fn to_board_works() {
+	let rules = get_rules();
+	let mut board = movement::place_ship(Board::default(), 0, 0, &rules.fleet[0], &Rotation::Vertical);
+	board = movement::place_ship(board, 5, 5, &rules.fleet[1], &Rotation::Horizontal);
+	board = movement::place_ship(board, 9, 9, &rules.fleet[1], &Rotation::Horizontal);
+	let preset = Preset::from_board("Spread", &board);
+	assert_eq!(preset.ships[0].rotation, Rotation::Vertical);
+	assert_eq!(preset.to_board(&rules).unwrap(), board);
+
+	let other_size = Rules {
+		size_x: 12,
+		..get_rules()
+	};
+	assert_eq!(preset.to_board(&other_size), Err(PresetError::WrongSize { size_x: 10, size_y: 10 }));
+
+	let mut missing = preset.clone();
+	missing.ships.pop();
+	assert_eq!(missing.to_board(&rules), Err(PresetError::MissingShips));
+
+	let mut too_many = preset.clone();
+	too_many.ships.push(too_many.ships[2].clone());
+	assert_eq!(too_many.to_board(&rules), Err(PresetError::UnknownShip(String::from("Patrol boat"))));
+
+	let mut touching = preset;
+	touching.ships[1].pos_x = 1;
+	touching.ships[1].pos_y = 0;
+	assert_eq!(
+		touching.to_board(&rules),
+		Err(PresetError::InvalidPlacement(String::from("Patrol boat"), PlacementError::Adjacent(0)))
+	);
+}
+
+#[test]
+fn save_and_load_work() {
+	let path = std::env::temp_dir().join(format!("battleship_presets_{}", std::process::id()));
+	let _ = fs::remove_file(&path);
+	assert_eq!(load(&path).unwrap(), vec![]);
+
+	let board = movement::place_ship(Board::default(), 0, 0, &get_rules().fleet[0], &Rotation::Horizontal);
+	save(&path, Preset::from_board("One", &board)).unwrap();
+	save(&path, Preset::from_board("Two", &Board::default())).unwrap();
+	save(&path, Preset::from_board("One", &Board::default())).unwrap();
+
+	let presets = load(&path).unwrap();
+	assert_eq!(presets.iter().map(|preset| preset.name.as_str()).collect::<Vec<_>>(), vec!["Two", "One"]);
+	assert!(presets[1].ships.is_empty());
+
+	fs::write(&path, "[Broken]\nsize 10\n").unwrap();
+	assert_eq!(load(&path), Err(PresetError::Syntax { line: 2 }));
+	assert!(save(&path, Preset::from_board("Three", &Board::default())).is_err());
+	fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,288 @@
+//! What the AI remembers about how I play across games, kept in a local file
+//!
+//! Every finished game adds where my ships were and where my first shots went. A game is written like a
+//! [`crate::preset`] followed by a line with its shots:
+//!
+//! ```text
+//! [game]
+//! size 10x10
+//! 0 0 horizontal Destroyer
+//! 9 7 vertical Patrol boat
+//! shots 4,4 5,5 0,0
+//! ```
+
+use crate::board::Board;
+use crate::config;
+use crate::movement;
+use crate::preset::{self, Preset, PresetError};
+use crate::ships::ShipClass;
+use crate::Rotation;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A game I played, where my ships were and my first shots in order
+#[derive(Clone, Debug, PartialEq)]
+pub struct PastGame {
+	pub layout: Preset,
+	pub shots: Vec<(usize, usize)>,
+}
+
+/// The latest games I played, oldest first
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profile {
+	pub games: Vec<PastGame>,
+}
+
+impl Profile {
+	/// Remember a game by the board with my ships and the shots I fired in order, only the latest
+	/// [`config::PROFILE_GAMES`] games and the first [`config::PROFILE_SHOTS`] shots of each are kept
+	pub fn record(&mut self, board_ships: &Board, shots: &[(usize, usize)]) {
+		self.games.push(PastGame {
+			layout: Preset::from_board("game", board_ships),
+			shots: shots.iter().take(config::PROFILE_SHOTS).copied().collect(),
+		});
+
+		let excess = self.games.len().saturating_sub(config::PROFILE_GAMES);
+		self.games.drain(..excess);
+	}
+
+	/// My habits from the games on a board of this size, ships of classes that are not part of the fleet are skipped
+	pub fn get_habits(&self, size_x: usize, size_y: usize, fleet: &[ShipClass]) -> Habits {
+		let mut habits = Habits {
+			size_x,
+			games: 0,
+			coverage: Vec::new(),
+			looks: vec![0; size_x * size_y],
+		};
+		let is_inside = |pos_x: usize, pos_y: usize| pos_x < size_x && pos_y < size_y;
+
+		for game in self.games.iter().filter(|game| (game.layout.size_x, game.layout.size_y) == (size_x, size_y)) {
+			habits.games += 1;
+
+			for ship in &game.layout.ships {
+				let kind = match fleet.iter().find(|kind| kind.name == ship.class) {
+					Some(kind) => kind,
+					None => continue,
+				};
+				let cells = movement::get_coordinates(ship.pos_x, ship.pos_y, kind.size, &ship.rotation);
+				let coverage = habits.get_coverage_mut(&kind.name, ship.rotation);
+				for (pos_x, pos_y) in cells.into_iter().filter(|(x, y)| is_inside(*x, *y)) {
+					coverage[pos_y * size_x + pos_x] += 1;
+				}
+			}
+
+			for (index, (pos_x, pos_y)) in game.shots.iter().take(config::PROFILE_SHOTS).enumerate() {
+				if is_inside(*pos_x, *pos_y) {
+					habits.looks[pos_y * size_x + pos_x] += (config::PROFILE_SHOTS - index) as u64;
+				}
+			}
+		}
+
+		habits
+	}
+}
+
+/// Where I put my ships and where I looked first in the past games on a board of one size
+#[derive(Clone, Debug, PartialEq)]
+pub struct Habits {
+	size_x: usize,
+	games: u64,
+	// how often a ship of a class in a rotation covered each cell
+	coverage: Vec<(String, Rotation, Vec<u64>)>,
+	looks: Vec<u64>,
+}
+
+impl Habits {
+	/// How many past games the habits are taken from
+	pub fn games(&self) -> u64 {
+		self.games
+	}
+
+	/// How much a placement of a ship counts, one that covers the cells I always put this class on in the same
+	/// rotation counts up to [`config::PROFILE_WEIGHT`] times as much as one I never used
+	pub fn get_prior(&self, kind: &ShipClass, cells: &[(usize, usize)]) -> u64 {
+		let rotation = match cells {
+			[(pos_x, _), (next_x, _), ..] if pos_x == next_x => Rotation::Vertical,
+			_ => Rotation::Horizontal,
+		};
+		let matches: u64 = match self.coverage.iter().find(|(name, turn, _)| *name == kind.name && *turn == rotation) {
+			Some((_, _, coverage)) => cells.iter().map(|(pos_x, pos_y)| coverage[pos_y * self.size_x + pos_x]).sum(),
+			None => 0,
+		};
+
+		self.games.max(1) * kind.size as u64 + (config::PROFILE_WEIGHT - 1) * matches
+	}
+
+	/// How early I used to shoot at a cell, the first shot of a game counts [`config::PROFILE_SHOTS`] and every shot
+	/// after it one less
+	pub fn get_looks(&self, pos_x: usize, pos_y: usize) -> u64 {
+		self.looks[pos_y * self.size_x + pos_x]
+	}
+
+	fn get_coverage_mut(&mut self, name: &str, rotation: Rotation) -> &mut Vec<u64> {
+		let index = match self.coverage.iter().position(|(other, turn, _)| other == name && *turn == rotation) {
+			Some(index) => index,
+			None => {
+				self.coverage.push((String::from(name), rotation, vec![0; self.looks.len()]));
+				self.coverage.len() - 1
+			}
+		};
+
+		&mut self.coverage[index].2
+	}
+}
+
+/// Read a profile from the text format
+pub fn parse(text: &str) -> Result<Profile, PresetError> {
+	// the shots are taken out line by line so the layouts keep their line numbers for errors
+	let mut layouts = String::new();
+	let mut shots: Vec<Vec<(usize, usize)>> = Vec::new();
+
+	for (index, line) in text.lines().enumerate() {
+		let line = line.trim();
+		let syntax = PresetError::Syntax { line: index + 1 };
+		if line.starts_with('[') {
+			shots.push(Vec::new());
+		}
+
+		match line.strip_prefix("shots").filter(|list| list.is_empty() || list.starts_with(' ')) {
+			Some(list) => {
+				let game = shots.last_mut().ok_or_else(|| syntax.clone())?;
+				*game = list.split_whitespace().map(parse_cell).collect::<Option<_>>().ok_or(syntax)?;
+			}
+			None => layouts += line,
+		}
+		layouts.push('\n');
+	}
+
+	let games =
+		preset::parse(&layouts)?.into_iter().zip(shots).map(|(layout, shots)| PastGame { layout, shots }).collect();
+
+	Ok(Profile { games })
+}
+
+fn parse_cell(cell: &str) -> Option<(usize, usize)> {
+	let (pos_x, pos_y) = cell.split_once(',')?;
+
+	Some((pos_x.parse().ok()?, pos_y.parse().ok()?))
+}
+
+/// Write a profile in the text format
+pub fn format(profile: &Profile) -> String {
+	let mut output = String::new();
+
+	for game in &profile.games {
+		let shots: Vec<String> = game.shots.iter().map(|(pos_x, pos_y)| format!("{},{}", pos_x, pos_y)).collect();
+		output += preset::format(std::slice::from_ref(&game.layout)).trim_end();
+		output += &format!("\nshots {}\n\n", shots.join(" "));
+	}
+
+	output
+}
+
+/// Read the profile from a file, a file that does not exist yet holds no games
+pub fn load(path: &Path) -> Result<Profile, PresetError> {
+	match fs::read_to_string(path) {
+		Ok(text) => parse(&text),
+		Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Profile::default()),
+		Err(error) => Err(PresetError::File(error.to_string())),
+	}
+}
+
+/// Write the profile to a file
+pub fn save(path: &Path, profile: &Profile) -> Result<(), PresetError> {
+	fs::write(path, format(profile)).map_err(|error| PresetError::File(error.to_string()))
+}
+
+/// Forget every game by removing the file
+pub fn reset(path: &Path) -> Result<(), PresetError> {
+	match fs::remove_file(path) {
+		Err(error) if error.kind() != io::ErrorKind::NotFound => Err(PresetError::File(error.to_string())),
+		_ => Ok(()),
+	}
+}
+
+#[cfg(test)]
+fn get_fleet() -> Vec<ShipClass> {
+	vec![ShipClass::new("Destroyer", 2, 1), ShipClass::new("Cruiser", 3, 1)]
+}
+
+#[cfg(test)]
+fn get_board() -> Board {
+	let fleet = get_fleet();
+	let board = movement::place_ship(Board::default(), 0, 0, &fleet[0], &Rotation::Vertical);
+	movement::place_ship(board, 5, 9, &fleet[1], &Rotation::Horizontal)
+}
+
+#[test]
+fn record_works() {
+	let mut profile = Profile::default();
+	let shots: Vec<(usize, usize)> = (0..20).map(|pos_x| (pos_x % 10, pos_x / 10)).collect();
+	for _ in 0..config::PROFILE_GAMES + 5 {
+		profile.record(&get_board(), &shots);
+	}
+
+	assert_eq!(profile.games.len(), config::PROFILE_GAMES);
+	assert_eq!(profile.games[0].shots, shots[..config::PROFILE_SHOTS]);
+	assert_eq!(profile.games[0].layout.ships[0].rotation, Rotation::Vertical);
+}
+
+#[test]
+fn parse_and_format_work() {
+	let text = "[game]\nsize 10x10\n0 0 vertical Destroyer\nshots 4,4 5,5\n\n[game]\nsize 9x4\nshots\n";
+	let profile = parse(text).unwrap();
+	assert_eq!(profile.games.len(), 2);
+	assert_eq!(profile.games[0].shots, vec![(4, 4), (5, 5)]);
+	assert_eq!(profile.games[0].layout.ships.len(), 1);
+	assert!(profile.games[1].shots.is_empty());
+	assert_eq!(parse(&format(&profile)).unwrap(), profile);
+
+	assert_eq!(parse("shots 1,1"), Err(PresetError::Syntax { line: 1 }));
+	assert_eq!(parse("[game]\nsize 10x10\nshots 1;1"), Err(PresetError::Syntax { line: 3 }));
+	assert_eq!(parse("[game]\nshots 1,1\nsize ten"), Err(PresetError::Syntax { line: 3 }));
+}
+
+#[test]
+fn habits_work() {
+	let fleet = get_fleet();
+	let mut profile = Profile::default();
+	profile.record(&get_board(), &[(9, 9), (0, 0)]);
+	profile.record(&get_board(), &[(9, 9)]);
+	profile.record(&Board::new(12, 10), &[(0, 0)]);
+
+	let habits = profile.get_habits(10, 10, &fleet);
+	assert_eq!(habits.games(), 2);
+
+	// the destroyer always stood upright in the corner
+	assert_eq!(habits.get_prior(&fleet[0], &[(0, 0), (0, 1)]), 2 * 2 + 3 * 4);
+	assert_eq!(habits.get_prior(&fleet[0], &[(0, 1), (0, 2)]), 2 * 2 + 3 * 2);
+	assert_eq!(habits.get_prior(&fleet[0], &[(0, 0), (1, 0)]), 2 * 2);
+	assert_eq!(habits.get_prior(&fleet[1], &[(6, 9), (7, 9), (8, 9)]), 2 * 3 + 3 * 4);
+
+	// the first shot of every game counts the most
+	assert_eq!(habits.get_looks(9, 9), 2 * config::PROFILE_SHOTS as u64);
+	assert_eq!(habits.get_looks(0, 0), config::PROFILE_SHOTS as u64 - 1);
+	assert_eq!(habits.get_looks(5, 5), 0);
+
+	// without games every placement counts the same
+	let habits = Profile::default().get_habits(10, 10, &fleet);
+	assert_eq!(habits.get_prior(&fleet[0], &[(0, 0), (0, 1)]), habits.get_prior(&fleet[0], &[(4, 4), (5, 4)]));
+}
+
+#[test]
+fn save_load_and_reset_work() {
+	let path = std::env::temp_dir().join(format!("battleship_profile_{}", std::process::id()));
+	let _ = fs::remove_file(&path);
+	assert_eq!(load(&path).unwrap(), Profile::default());
+
+	let mut profile = Profile::default();
+	profile.record(&get_board(), &[(1, 2)]);
+	save(&path, &profile).unwrap();
+	assert_eq!(load(&path).unwrap(), profile);
+
+	reset(&path).unwrap();
+	assert_eq!(load(&path).unwrap(), Profile::default());
+	reset(&path).unwrap();
+}
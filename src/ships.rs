@@ -1,91 +1,307 @@
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum Ship {
-	TwoBlock,
-	ThreeBlock,
-	FourBlock
+//! Fleet definitions and the ships placed on a board
+
+/// A class of ship within a fleet e.g. 2 Destroyers of length 2
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShipClass {
+	pub name: String,
+	pub size: usize,
+	pub amount: usize,
+}
+
+impl ShipClass {
+	pub fn new(name: &str, size: usize, amount: usize) -> Self {
+		Self {
+			name: String::from(name),
+			size,
+			amount,
+		}
+	}
+}
+
+/// The amount of blocks all ships of a fleet take up together
+pub fn get_fleet_size(fleet: &[ShipClass]) -> usize {
+	fleet.iter().map(|kind| kind.size * kind.amount).sum()
 }
 
+#[test]
+fn get_fleet_size_works() {
+	assert_eq!(get_fleet_size(&[]), 0);
+	assert_eq!(get_fleet_size(&[ShipClass::new("Destroyer", 2, 3)]), 6);
+	assert_eq!(
+		get_fleet_size(&[
+			ShipClass::new("Carrier", 5, 1),
+			ShipClass::new("Battleship", 4, 1),
+			ShipClass::new("Cruiser", 3, 1),
+			ShipClass::new("Submarine", 3, 1),
+			ShipClass::new("Destroyer", 2, 1),
+		]),
+		17
+	);
+}
+
+/// Keeps track of which ships of a fleet still need to be placed
 #[derive(Debug)]
 pub struct ShipTracker {
-	two_block: usize,
-	three_block: usize,
-	four_block: usize,
+	fleet: Vec<ShipClass>,
+	remaining: Vec<usize>,
 }
 
 impl ShipTracker {
-	pub fn new(two_block: usize, three_block: usize, four_block: usize) -> Self {
+	pub fn new(fleet: &[ShipClass]) -> Self {
 		Self {
-			two_block,
-			three_block,
-			four_block,
+			fleet: fleet.to_vec(),
+			remaining: fleet.iter().map(|kind| kind.amount).collect(),
 		}
 	}
 
-	pub fn get_next_unset_ship(&self) -> Option<Ship> {
-		if self.two_block > 0 {
-			Some(Ship::TwoBlock)
-		} else if self.three_block > 0 {
-			Some(Ship::ThreeBlock)
-		} else if self.four_block > 0 {
-			Some(Ship::FourBlock)
-		} else {
-			None
+	pub fn get_next_unset_ship(&self) -> Option<ShipClass> {
+		self.fleet.iter().zip(self.remaining.iter()).find(|(_, remaining)| **remaining > 0).map(|(kind, _)| kind.clone())
+	}
+
+	pub fn set_ship(&mut self, kind: &ShipClass) {
+		if let Some(index) = self.fleet.iter().position(|item| item.name == kind.name) {
+			self.remaining[index] -= 1;
 		}
 	}
 
-	pub fn set_ship(&mut self, kind: &Ship) {
-		match kind {
-			Ship::TwoBlock => {
-				self.two_block -= 1;
-			}
-			Ship::ThreeBlock => {
-				self.three_block -= 1;
-			}
-			Ship::FourBlock => {
-				self.four_block -= 1;
-			}
+	/// Put a ship back onto the list of ships that still need to be placed
+	pub fn unset_ship(&mut self, kind: &ShipClass) {
+		if let Some(index) = self.fleet.iter().position(|item| item.name == kind.name) {
+			self.remaining[index] = (self.remaining[index] + 1).min(self.fleet[index].amount);
 		}
 	}
 
-	pub fn get_ships(&self) -> (usize, usize, usize) {
-		(self.two_block, self.three_block, self.four_block)
+	/// The amount of ships left to be placed per class in the order of the fleet
+	pub fn get_ships(&self) -> Vec<usize> {
+		self.remaining.clone()
+	}
+
+	/// The ships that still need to be placed as a fleet of their own
+	pub fn get_unset_fleet(&self) -> Vec<ShipClass> {
+		self
+			.fleet
+			.iter()
+			.zip(self.remaining.iter())
+			.filter(|(_, remaining)| **remaining > 0)
+			.map(|(kind, remaining)| ShipClass::new(&kind.name, kind.size, *remaining))
+			.collect()
+	}
+
+	pub fn get_remaining(&self) -> usize {
+		self.remaining.iter().sum()
 	}
 }
 
 #[test]
 fn keep_track_of_ships() {
-	let mut ships = ShipTracker::new(3, 2, 1);
+	let destroyer = ShipClass::new("Destroyer", 2, 3);
+	let cruiser = ShipClass::new("Cruiser", 3, 2);
+	let battleship = ShipClass::new("Battleship", 4, 1);
+	let mut ships = ShipTracker::new(&[destroyer.clone(), cruiser.clone(), battleship.clone()]);
 
-	assert_eq!(ships.get_ships(), (3, 2, 1));
+	assert_eq!(ships.get_ships(), vec![3, 2, 1]);
+	assert_eq!(ships.get_remaining(), 6);
 	let mut this_ship = ships.get_next_unset_ship().unwrap();
-	assert_eq!(this_ship, Ship::TwoBlock);
+	assert_eq!(this_ship, destroyer);
 	ships.set_ship(&this_ship);
 
-	assert_eq!(ships.get_ships(), (2, 2, 1));
+	assert_eq!(ships.get_ships(), vec![2, 2, 1]);
 	this_ship = ships.get_next_unset_ship().unwrap();
-	assert_eq!(this_ship, Ship::TwoBlock);
+	assert_eq!(this_ship, destroyer);
 	ships.set_ship(&this_ship);
 
-	assert_eq!(ships.get_ships(), (1, 2, 1));
+	assert_eq!(ships.get_ships(), vec![1, 2, 1]);
 	this_ship = ships.get_next_unset_ship().unwrap();
-	assert_eq!(this_ship, Ship::TwoBlock);
+	assert_eq!(this_ship, destroyer);
 	ships.set_ship(&this_ship);
 
-	assert_eq!(ships.get_ships(), (0, 2, 1));
+	assert_eq!(ships.get_ships(), vec![0, 2, 1]);
+	assert_eq!(ships.get_unset_fleet(), vec![ShipClass::new("Cruiser", 3, 2), ShipClass::new("Battleship", 4, 1)]);
 	this_ship = ships.get_next_unset_ship().unwrap();
-	assert_eq!(this_ship, Ship::ThreeBlock);
+	assert_eq!(this_ship, cruiser);
 	ships.set_ship(&this_ship);
 
-	assert_eq!(ships.get_ships(), (0, 1, 1));
+	assert_eq!(ships.get_ships(), vec![0, 1, 1]);
 	this_ship = ships.get_next_unset_ship().unwrap();
-	assert_eq!(this_ship, Ship::ThreeBlock);
+	assert_eq!(this_ship, cruiser);
 	ships.set_ship(&this_ship);
 
-	assert_eq!(ships.get_ships(), (0, 0, 1));
+	assert_eq!(ships.get_ships(), vec![0, 0, 1]);
 	this_ship = ships.get_next_unset_ship().unwrap();
-	assert_eq!(this_ship, Ship::FourBlock);
+	assert_eq!(this_ship, battleship);
 	ships.set_ship(&this_ship);
 
-	assert_eq!(ships.get_ships(), (0, 0, 0));
-	assert!(ships.get_next_unset_ship().is_none())
+	assert_eq!(ships.get_ships(), vec![0, 0, 0]);
+	assert_eq!(ships.get_remaining(), 0);
+	assert!(ships.get_next_unset_ship().is_none());
+
+	ships.unset_ship(&cruiser);
+	assert_eq!(ships.get_ships(), vec![0, 1, 0]);
+	assert_eq!(ships.get_next_unset_ship().unwrap(), cruiser);
+	ships.unset_ship(&cruiser);
+	ships.unset_ship(&cruiser);
+	assert_eq!(ships.get_ships(), vec![0, 2, 0]);
+}
+
+#[test]
+fn keep_track_of_ships_of_any_length() {
+	let mut ships = ShipTracker::new(&[ShipClass::new("Carrier", 5, 1), ShipClass::new("Patrol boat", 1, 2)]);
+
+	let this_ship = ships.get_next_unset_ship().unwrap();
+	assert_eq!(this_ship.name, "Carrier");
+	assert_eq!(this_ship.size, 5);
+	ships.set_ship(&this_ship);
+
+	let this_ship = ships.get_next_unset_ship().unwrap();
+	assert_eq!(this_ship.name, "Patrol boat");
+	assert_eq!(this_ship.size, 1);
+	ships.set_ship(&this_ship);
+	assert_eq!(ships.get_ships(), vec![0, 1]);
+}
+
+/// Identifies a placed ship within the fleet registry of its board
+pub type ShipId = usize;
+
+/// A ship that has been placed on a board, `damage` lines up with `cells`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlacedShip {
+	pub id: ShipId,
+	pub class: ShipClass,
+	pub cells: Vec<(usize, usize)>,
+	pub damage: Vec<bool>,
+}
+
+impl PlacedShip {
+	pub fn contains(&self, pos_x: usize, pos_y: usize) -> bool {
+		self.cells.contains(&(pos_x, pos_y))
+	}
+
+	pub fn is_sunk(&self) -> bool {
+		self.damage.iter().all(|damaged| *damaged)
+	}
+}
+
+/// All ships placed on a board, cells refer to them via their id
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FleetRegistry {
+	ships: Vec<PlacedShip>,
+	next_id: ShipId,
+}
+
+impl FleetRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn add(&mut self, class: &ShipClass, cells: Vec<(usize, usize)>) -> ShipId {
+		let id = self.next_id;
+		self.next_id += 1;
+
+		self.ships.push(PlacedShip {
+			id,
+			class: class.clone(),
+			damage: vec![false; cells.len()],
+			cells,
+		});
+
+		id
+	}
+
+	/// Add a ship under an id of its own, none when a ship already goes by it, later ids are handed out after it
+	pub fn add_with_id(&mut self, id: ShipId, class: &ShipClass, cells: Vec<(usize, usize)>) -> Option<ShipId> {
+		if self.get(id).is_some() {
+			return None;
+		}
+
+		let next_id = self.next_id;
+		self.next_id = id;
+		self.add(class, cells);
+		self.next_id = next_id.max(id + 1);
+
+		Some(id)
+	}
+
+	/// The id the next ship that is added gets
+	pub fn next_id(&self) -> ShipId {
+		self.next_id
+	}
+
+	/// Hand out no id below this one from now on, as if the ships before it had been removed
+	pub fn skip_ids(&mut self, next_id: ShipId) {
+		self.next_id = self.next_id.max(next_id);
+	}
+
+	/// Take a ship off the board, its id is never handed out again
+	pub fn remove(&mut self, id: ShipId) -> Option<PlacedShip> {
+		let index = self.ships.iter().position(|ship| ship.id == id)?;
+
+		Some(self.ships.remove(index))
+	}
+
+	pub fn get(&self, id: ShipId) -> Option<&PlacedShip> {
+		self.ships.iter().find(|ship| ship.id == id)
+	}
+
+	pub fn get_at(&self, pos_x: usize, pos_y: usize) -> Option<&PlacedShip> {
+		self.ships.iter().find(|ship| ship.contains(pos_x, pos_y))
+	}
+
+	pub fn get_ships(&self) -> &[PlacedShip] {
+		&self.ships
+	}
+
+	/// Mark the ship at the given coordinates as damaged and return its id
+	pub fn record_hit(&mut self, pos_x: usize, pos_y: usize) -> Option<ShipId> {
+		let ship = self.ships.iter_mut().find(|ship| ship.contains(pos_x, pos_y))?;
+		let index = ship.cells.iter().position(|cell| *cell == (pos_x, pos_y))?;
+		ship.damage[index] = true;
+
+		Some(ship.id)
+	}
+
+	/// Whether every ship has been sunk
+	pub fn is_defeated(&self) -> bool {
+		self.ships.iter().all(|ship| ship.is_sunk())
+	}
+}
+
+#[test]
+fn fleet_registry_works() {
+	let mut registry = FleetRegistry::new();
+	let destroyer = registry.add(&ShipClass::new("Destroyer", 2, 1), vec![(0, 0), (1, 0)]);
+	let carrier = registry.add(&ShipClass::new("Carrier", 5, 1), vec![(3, 2), (3, 3), (3, 4), (3, 5), (3, 6)]);
+	assert_ne!(destroyer, carrier);
+
+	assert_eq!(registry.get(carrier).unwrap().class.name, "Carrier");
+	assert_eq!(registry.get_at(1, 0).unwrap().id, destroyer);
+	assert_eq!(registry.get_at(3, 6).unwrap().id, carrier);
+	assert!(registry.get_at(2, 0).is_none());
+
+	assert_eq!(registry.record_hit(9, 9), None);
+	assert_eq!(registry.record_hit(0, 0), Some(destroyer));
+	assert!(!registry.get(destroyer).unwrap().is_sunk());
+	assert_eq!(registry.record_hit(1, 0), Some(destroyer));
+	assert!(registry.get(destroyer).unwrap().is_sunk());
+	assert!(!registry.is_defeated());
+
+	for y in 2..=6 {
+		registry.record_hit(3, y);
+	}
+	assert!(registry.get(carrier).unwrap().is_sunk());
+	assert!(registry.is_defeated());
+
+	assert_eq!(registry.remove(destroyer).unwrap().class.name, "Destroyer");
+	assert!(registry.remove(destroyer).is_none());
+	assert!(registry.get_at(0, 0).is_none());
+	let cruiser = registry.add(&ShipClass::new("Cruiser", 3, 1), vec![(0, 0), (1, 0), (2, 0)]);
+	assert_ne!(cruiser, destroyer);
+	assert_eq!(registry.get_ships().len(), 2);
+
+	assert_eq!(registry.add_with_id(cruiser, &ShipClass::new("Cruiser", 3, 1), vec![(0, 9)]), None);
+	assert_eq!(registry.add_with_id(7, &ShipClass::new("Cruiser", 3, 1), vec![(0, 9)]), Some(7));
+	assert_eq!(registry.next_id(), 8);
+	registry.skip_ids(5);
+	assert_eq!(registry.next_id(), 8);
+	registry.skip_ids(10);
+	assert_eq!(registry.add(&ShipClass::new("Cruiser", 3, 1), vec![(1, 9)]), 10);
 }
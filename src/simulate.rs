@@ -0,0 +1,487 @@
+//! Many seeded games between AI strategies and bots without a terminal, to measure how well they play
+//!
+//! A duel plays whole games through the [`engine::Game`] with one strategy on each side, the sides swap every game so
+//! neither always shoots first. A strategy can also shoot at fixed layouts on its own to see how many shots it needs.
+
+use crate::ai::{self, Difficulty, FleetError, Observation, Strategy};
+use crate::board::Board;
+use crate::bot::Bot;
+use crate::config;
+use crate::engine::{self, Command, CommandError, Game, Phase, Rules};
+use crate::history::Actor;
+use crate::Cell;
+use crate::GameRng;
+
+use rand::SeedableRng;
+
+/// Who plays one side of a simulation
+#[derive(Clone, Debug, PartialEq)]
+pub enum Player {
+	Ai(Difficulty),
+	/// A bot started with this shell command, see [`crate::bot`]
+	Bot(String),
+}
+
+impl Player {
+	/// The name of the player in reports
+	pub fn name(&self) -> &str {
+		match self {
+			Player::Ai(difficulty) => difficulty.name(),
+			Player::Bot(command) => command,
+		}
+	}
+
+	/// A fresh strategy for a new game, the adaptive AI learns from the profile in the rules
+	pub fn get_strategy(&self, rules: &Rules) -> Box<dyn Strategy> {
+		match self {
+			Player::Ai(difficulty) => ai::get_strategy(*difficulty, &rules.profile),
+			Player::Bot(command) => Box::new(Bot::new(command, config::BOT_TIMEOUT)),
+		}
+	}
+}
+
+/// How a single game of a simulation ended
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Record {
+	pub seed: u64,
+	/// The index of the strategy in [`Report::strategies`] that won, none when a strategy shooting at a fixed layout
+	/// forfeited
+	pub winner: Option<usize>,
+	/// The shots the winner fired
+	pub shots: usize,
+	/// The game ended because a strategy gave up, its shots don't count towards the stats
+	pub forfeit: bool,
+}
+
+/// The shots a strategy needed in the games it won without a forfeit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+	pub mean: f64,
+	pub median: f64,
+	pub p95: usize,
+	pub min: usize,
+	pub max: usize,
+	pub std_dev: f64,
+}
+
+impl Stats {
+	/// The stats of a list of shot counts, none for an empty list
+	pub fn new(shots: &[usize]) -> Option<Self> {
+		if shots.is_empty() {
+			return None;
+		}
+
+		let mut sorted = shots.to_vec();
+		sorted.sort_unstable();
+		let count = sorted.len();
+		let mean = sorted.iter().sum::<usize>() as f64 / count as f64;
+		let median = if count.is_multiple_of(2) {
+			(sorted[count / 2 - 1] + sorted[count / 2]) as f64 / 2.0
+		} else {
+			sorted[count / 2] as f64
+		};
+		// the nearest rank, so 95 percent of the games took this many shots or fewer
+		let p95 = sorted[(count * 95).div_ceil(100) - 1];
+		let variance = sorted.iter().map(|shots| (*shots as f64 - mean).powi(2)).sum::<f64>() / count as f64;
+
+		Some(Self {
+			mean,
+			median,
+			p95,
+			min: sorted[0],
+			max: sorted[count - 1],
+			std_dev: variance.sqrt(),
+		})
+	}
+}
+
+/// Every game of a simulation in the order it was played
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+	pub strategies: Vec<Player>,
+	pub records: Vec<Record>,
+}
+
+impl Report {
+	/// The games this strategy won
+	pub fn get_wins(&self, strategy: usize) -> usize {
+		self.records.iter().filter(|record| record.winner == Some(strategy)).count()
+	}
+
+	/// The share of all games this strategy won
+	pub fn get_win_rate(&self, strategy: usize) -> f64 {
+		if self.records.is_empty() {
+			return 0.0;
+		}
+
+		self.get_wins(strategy) as f64 / self.records.len() as f64
+	}
+
+	/// The shots this strategy needed in the games it won without a forfeit
+	pub fn get_stats(&self, strategy: usize) -> Option<Stats> {
+		Stats::new(&self.get_shots(strategy))
+	}
+
+	/// The mean shots this strategy needed in each of `blocks` runs of consecutive games, to see how results drift
+	/// over the simulation
+	pub fn get_trend(&self, strategy: usize, blocks: usize) -> Vec<Option<f64>> {
+		let size = self.records.len().div_ceil(blocks.max(1)).max(1);
+
+		self.records.chunks(size).map(|chunk| Stats::new(&get_shots(chunk, strategy)).map(|stats| stats.mean)).collect()
+	}
+
+	fn get_shots(&self, strategy: usize) -> Vec<usize> {
+		get_shots(&self.records, strategy)
+	}
+}
+
+fn get_shots(records: &[Record], strategy: usize) -> Vec<usize> {
+	records
+		.iter()
+		.filter(|record| record.winner == Some(strategy) && !record.forfeit)
+		.map(|record| record.shots)
+		.collect()
+}
+
+/// Play `games` games between two strategies, game `n` is played with the seed `seed + n`
+///
+/// Fails when the fleet can't be placed on the board of the rules.
+pub fn run_duel(rules: &Rules, first: &Player, second: &Player, games: usize, seed: u64) -> Result<Report, FleetError> {
+	let mut records = Vec::new();
+
+	for index in 0..games {
+		let game_seed = seed.wrapping_add(index as u64);
+		// the first strategy plays my side in even games and the side of the AI in odd ones
+		let sides = if index.is_multiple_of(2) {
+			[first, second]
+		} else {
+			[second, first]
+		};
+		let (winner, shots, forfeit) = play_duel(rules, sides, game_seed)?;
+		let winner = if (index.is_multiple_of(2)) == (winner == Actor::Me) {
+			0
+		} else {
+			1
+		};
+
+		records.push(Record {
+			seed: game_seed,
+			winner: Some(winner),
+			shots,
+			forfeit,
+		});
+	}
+
+	Ok(Report {
+		strategies: vec![first.clone(), second.clone()],
+		records,
+	})
+}
+
+/// Let a strategy shoot at each of the layouts in turn until `games` games are played, it wins every game
+pub fn run_layouts(rules: &Rules, player: &Player, layouts: &[Board], games: usize, seed: u64) -> Report {
+	let mut records = Vec::new();
+
+	for (index, layout) in layouts.iter().cycle().take(games).enumerate() {
+		let game_seed = seed.wrapping_add(index as u64);
+		let (shots, forfeit) = sink_layout(rules, player, layout.clone(), game_seed);
+		records.push(Record {
+			seed: game_seed,
+			winner: if forfeit { None } else { Some(0) },
+			shots,
+			forfeit,
+		});
+	}
+
+	Report {
+		strategies: vec![player.clone()],
+		records,
+	}
+}
+
+// one game through the engine, returns the winner, the shots they fired and whether the other side forfeited
+fn play_duel(rules: &Rules, [me, ai]: [&Player; 2], seed: u64) -> Result<(Actor, usize, bool), FleetError> {
+	let rules = Rules {
+		seed: Some(seed),
+		..rules.clone()
+	};
+	let (mut strategy, ai_strategy) = (me.get_strategy(&rules), ai.get_strategy(&rules));
+	let mut game = match Game::with_strategy(rules, ai_strategy) {
+		Err(FleetError::Forfeit(_)) => return Ok((Actor::Me, 0, true)),
+		result => result?,
+	};
+
+	match game.place_fleet_with(strategy.as_mut()) {
+		Err(CommandError::Fleet(FleetError::Forfeit(_))) => return Ok((Actor::Ai, 0, true)),
+		Err(CommandError::Fleet(error)) => return Err(error),
+		result => result.expect("a new game is placing ships"),
+	};
+	game.execute(Command::Confirm).expect("every ship is placed");
+
+	let (mut shots_me, mut shots_ai, mut forfeit) = (0, 0, false);
+	while game.phase() == Phase::Battle {
+		let result = match game.turn() {
+			Actor::Me => game.play_my_turn(strategy.as_mut()),
+			Actor::Ai => game.play_ai_turn(),
+		};
+		match result {
+			Ok(engine::Shot { actor: Actor::Me, .. }) => shots_me += 1,
+			Ok(engine::Shot { actor: Actor::Ai, .. }) => shots_ai += 1,
+			Err(CommandError::Forfeit(_)) => forfeit = true,
+			Err(error) => unreachable!("a strategy was asked to shoot out of turn: {:?}", error),
+		}
+	}
+
+	let winner = match game.phase() {
+		Phase::Finished(winner) => winner,
+		_ => unreachable!("the battle only ends with a winner"),
+	};
+	strategy.game_over(winner == Actor::Me);
+
+	match winner {
+		Actor::Me => Ok((Actor::Me, shots_me, forfeit)),
+		Actor::Ai => Ok((Actor::Ai, shots_ai, forfeit)),
+	}
+}
+
+// the shots a strategy needs to sink a fleet nobody shoots back from and whether it forfeited on the way
+fn sink_layout(rules: &Rules, player: &Player, mut board_ships: Board, seed: u64) -> (usize, bool) {
+	let mut rng = GameRng::seed_from_u64(seed);
+	let mut strategy = player.get_strategy(rules);
+	let mut board_shots = Board::new(board_ships.size_x(), board_ships.size_y());
+	let mut shots = 0;
+
+	while !board_ships.fleet().is_defeated() {
+		let observation = Observation::new(&board_shots, &board_ships, rules);
+		let (pos_x, pos_y) = strategy.next_shot(&observation, &mut rng);
+		if strategy.get_forfeit().is_some() || !observation.is_open(pos_x, pos_y) {
+			return (shots, true);
+		}
+
+		board_shots[pos_y][pos_x] = match board_ships.fleet_mut().record_hit(pos_x, pos_y) {
+			Some(_) => Cell::Damage,
+			None => Cell::Shot,
+		};
+		shots += 1;
+	}
+	strategy.game_over(true);
+
+	(shots, false)
+}
+
+/// The report as a plain table with one row per strategy and the mean shots over ten runs of games below it
+pub fn format_table(report: &Report) -> String {
+	// bot commands can be longer than the names of the AI levels
+	let width = report.strategies.iter().map(|player| player.name().chars().count()).fold(10, usize::max);
+	let mut output = format!(
+		"{:<width$} {:>6} {:>9} {:>7} {:>7} {:>5} {:>5} {:>5} {:>8}\n",
+		"Strategy", "Wins", "Win rate", "Mean", "Median", "P95", "Min", "Max", "Std dev"
+	);
+
+	for (index, player) in report.strategies.iter().enumerate() {
+		output +=
+			&format!("{:<width$} {:>6} {:>8.1}% ", player.name(), report.get_wins(index), report.get_win_rate(index) * 100.0);
+		output += &match report.get_stats(index) {
+			Some(stats) => format!(
+				"{:>7.1} {:>7.1} {:>5} {:>5} {:>5} {:>8.1}\n",
+				stats.mean, stats.median, stats.p95, stats.min, stats.max, stats.std_dev
+			),
+			None => format!("{:>7} {:>7} {:>5} {:>5} {:>5} {:>8}\n", "-", "-", "-", "-", "-", "-"),
+		};
+	}
+
+	output.push('\n');
+	for (index, player) in report.strategies.iter().enumerate() {
+		let trend: Vec<String> = report
+			.get_trend(index, 10)
+			.iter()
+			.map(|mean| mean.map_or(String::from("-"), |mean| format!("{:.1}", mean)))
+			.collect();
+		output += &format!("{:<width$} mean shots over time: {}\n", player.name(), trend.join(" "));
+	}
+
+	output
+}
+
+/// The report as JSON with the stats of every strategy and every single game
+pub fn format_json(report: &Report) -> String {
+	let strategies: Vec<String> = report
+		.strategies
+		.iter()
+		.enumerate()
+		.map(|(index, player)| {
+			let shots = match report.get_stats(index) {
+				Some(stats) => format!(
+					"{{\"mean\":{:.3},\"median\":{:.1},\"p95\":{},\"min\":{},\"max\":{},\"std_dev\":{:.3}}}",
+					stats.mean, stats.median, stats.p95, stats.min, stats.max, stats.std_dev
+				),
+				None => String::from("null"),
+			};
+
+			format!(
+				"{{\"name\":\"{}\",\"wins\":{},\"win_rate\":{:.3},\"shots\":{}}}",
+				escape(player.name()),
+				report.get_wins(index),
+				report.get_win_rate(index),
+				shots
+			)
+		})
+		.collect();
+	let games: Vec<String> = report
+		.records
+		.iter()
+		.map(|record| {
+			let winner = match record.winner {
+				Some(winner) => format!("\"{}\"", escape(report.strategies[winner].name())),
+				None => String::from("null"),
+			};
+			format!(
+				"{{\"seed\":{},\"winner\":{},\"shots\":{},\"forfeit\":{}}}",
+				record.seed, winner, record.shots, record.forfeit
+			)
+		})
+		.collect();
+
+	format!("{{\"strategies\":[{}],\"games\":[{}]}}\n", strategies.join(","), games.join(","))
+}
+
+// a JSON string body, bot commands can contain quotes and backslashes
+fn escape(text: &str) -> String {
+	text
+		.chars()
+		.map(|char| match char {
+			'"' => String::from("\\\""),
+			'\\' => String::from("\\\\"),
+			char if char.is_control() => format!("\\u{:04x}", char as u32),
+			char => char.to_string(),
+		})
+		.collect()
+}
+
+#[cfg(test)]
+use crate::{
+	notation,
+	ships::{self, ShipClass},
+};
+
+#[test]
+fn stats_works() {
+	assert_eq!(Stats::new(&[]), None);
+
+	let stats = Stats::new(&[40, 30, 50, 20]).unwrap();
+	assert_eq!(stats.mean, 35.0);
+	assert_eq!(stats.median, 35.0);
+	assert_eq!(stats.p95, 50);
+	assert_eq!((stats.min, stats.max), (20, 50));
+	assert_eq!(stats.std_dev, 125_f64.sqrt());
+
+	let shots: Vec<usize> = (1..=100).collect();
+	let stats = Stats::new(&shots).unwrap();
+	assert_eq!(stats.median, 50.5);
+	assert_eq!(stats.p95, 95);
+}
+
+#[test]
+fn run_duel_works() {
+	let rules = Rules {
+		fleet: config::get_fleet(),
+		..Rules::default()
+	};
+
+	let report = run_duel(&rules, &Player::Ai(Difficulty::Hard), &Player::Ai(Difficulty::Easy), 20, 1).unwrap();
+	assert_eq!(report.records.len(), 20);
+	assert_eq!(report.records[3].seed, 4);
+	assert_eq!(report.get_wins(0) + report.get_wins(1), 20);
+	assert!(report.get_wins(0) > report.get_wins(1));
+	assert!(report.records.iter().all(|record| record.shots >= ships::get_fleet_size(&rules.fleet)));
+
+	// the same seed plays the same games
+	assert_eq!(report, run_duel(&rules, &Player::Ai(Difficulty::Hard), &Player::Ai(Difficulty::Easy), 20, 1).unwrap());
+
+	let rules = Rules {
+		size_x: 6,
+		size_y: 6,
+		fleet: vec![ShipClass::new("Carrier", 7, 1)],
+		..Rules::default()
+	};
+	assert_eq!(
+		run_duel(&rules, &Player::Ai(Difficulty::Hard), &Player::Ai(Difficulty::Easy), 1, 1),
+		Err(FleetError::ShipTooLong(String::from("Carrier")))
+	);
+}
+
+#[test]
+fn run_layouts_works() {
+	let rules = Rules {
+		size_x: 4,
+		size_y: 4,
+		fleet: vec![ShipClass::new("Destroyer", 2, 1)],
+		..Rules::default()
+	};
+	let layouts = vec![
+		notation::parse("AA..\n....\n....\n....\n").unwrap(),
+		notation::parse("....\n....\n....\n..AA\n").unwrap(),
+	];
+
+	let report = run_layouts(&rules, &Player::Ai(Difficulty::Hard), &layouts, 5, 9);
+	assert_eq!(report.records.len(), 5);
+	assert_eq!(report.get_win_rate(0), 1.0);
+	assert!(report.records.iter().all(|record| (2..=16).contains(&record.shots)));
+	assert_eq!(report, run_layouts(&rules, &Player::Ai(Difficulty::Hard), &layouts, 5, 9));
+}
+
+#[test]
+fn format_works() {
+	let report = Report {
+		strategies: vec![Player::Ai(Difficulty::Hard), Player::Ai(Difficulty::Easy)],
+		records: vec![
+			Record {
+				seed: 1,
+				winner: Some(0),
+				shots: 40,
+				forfeit: false,
+			},
+			Record {
+				seed: 2,
+				winner: Some(0),
+				shots: 50,
+				forfeit: false,
+			},
+		],
+	};
+
+	assert_eq!(
+		format_table(&report),
+		"Strategy     Wins  Win rate    Mean  Median   P95   Min   Max  Std dev\n\
+		 hard            2    100.0%    45.0    45.0    50    40    50      5.0\n\
+		 easy            0      0.0%       -       -     -     -     -        -\n\
+		 \n\
+		 hard       mean shots over time: 40.0 50.0\n\
+		 easy       mean shots over time: - -\n"
+	);
+	assert_eq!(
+		format_json(&report),
+		"{\"strategies\":[\
+		 {\"name\":\"hard\",\"wins\":2,\"win_rate\":1.000,\"shots\":{\"mean\":45.000,\"median\":45.0,\"p95\":50,\"min\":40,\"max\":50,\"std_dev\":5.000}},\
+		 {\"name\":\"easy\",\"wins\":0,\"win_rate\":0.000,\"shots\":null}],\
+		 \"games\":[{\"seed\":1,\"winner\":\"hard\",\"shots\":40,\"forfeit\":false},\
+		 {\"seed\":2,\"winner\":\"hard\",\"shots\":50,\"forfeit\":false}]}\n"
+	);
+
+	// a forfeit counts as a win but not towards the shots, bot commands are escaped
+	let report = Report {
+		strategies: vec![Player::Bot(String::from("./bot \"x\"")), Player::Ai(Difficulty::Easy)],
+		records: vec![Record {
+			seed: 1,
+			winner: Some(1),
+			shots: 3,
+			forfeit: true,
+		}],
+	};
+	assert_eq!(report.get_wins(1), 1);
+	assert_eq!(report.get_stats(1), None);
+	assert!(format_json(&report).starts_with("{\"strategies\":[{\"name\":\"./bot \\\"x\\\"\","));
+	assert!(
+		format_json(&report).ends_with("\"games\":[{\"seed\":1,\"winner\":\"easy\",\"shots\":3,\"forfeit\":true}]}\n")
+	);
+}
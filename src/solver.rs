@@ -0,0 +1,381 @@
+//! Hit probabilities from every arrangement of the remaining fleet that agrees with what was seen so far
+//!
+//! Arrangements are enumerated exactly while that fits into the [`Budget`], bigger searches fall back to sampling
+//! random arrangements instead.
+
+use crate::ai::{self, Observation};
+use crate::movement;
+use crate::ships::ShipClass;
+use crate::Cell;
+use crate::GameRng;
+
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+/// How much work the solver may do for one observation
+///
+/// The work is counted rather than timed so the same observation and random numbers always give the same result on
+/// any machine. Analysis tools can put a wall clock on top, a search that runs out of it depends on the machine.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Budget {
+	/// How many steps counting every arrangement may take before the solver samples instead
+	pub nodes: u64,
+	/// How many random arrangements are drawn when sampling, only the legal ones are kept
+	pub samples: u64,
+	/// How long the whole search may take at most, half of it goes into counting
+	pub time: Option<Duration>,
+}
+
+/// The share of arrangements that put a ship on each cell
+#[derive(Clone, Debug, PartialEq)]
+pub struct Posterior {
+	size_x: usize,
+	size_y: usize,
+	covered: Vec<u64>,
+	arrangements: u64,
+	is_exact: bool,
+}
+
+impl Posterior {
+	/// The probability that a ship is on this cell, 0 when no arrangement was found
+	pub fn get(&self, pos_x: usize, pos_y: usize) -> f64 {
+		if self.arrangements == 0 {
+			return 0.0;
+		}
+
+		self.covered[pos_y * self.size_x + pos_x] as f64 / self.arrangements as f64
+	}
+
+	/// How many arrangements the probabilities are based on
+	pub fn arrangements(&self) -> u64 {
+		self.arrangements
+	}
+
+	/// Whether every arrangement was counted instead of a sample of them
+	pub fn is_exact(&self) -> bool {
+		self.is_exact
+	}
+
+	/// All cells that were not shot at yet and share the highest probability
+	pub fn get_best(&self, observation: &Observation) -> Vec<(usize, usize)> {
+		let open: Vec<(usize, usize)> = (0..self.size_y)
+			.flat_map(|pos_y| (0..self.size_x).map(move |pos_x| (pos_x, pos_y)))
+			.filter(|(pos_x, pos_y)| observation.is_open(*pos_x, *pos_y))
+			.collect();
+		let max = open.iter().map(|(pos_x, pos_y)| self.covered[pos_y * self.size_x + pos_x]).max().unwrap_or(0);
+
+		open.into_iter().filter(|(pos_x, pos_y)| self.covered[pos_y * self.size_x + pos_x] == max).collect()
+	}
+}
+
+// a spot a ship could take, `halo` holds the cells the adjacency rule keeps free of other ships
+struct Placement {
+	cells: Vec<usize>,
+	halo: Vec<usize>,
+}
+
+// how many of the placed ships take up or keep free each cell
+struct Grid {
+	occupied: Vec<u32>,
+	forbidden: Vec<u32>,
+}
+
+impl Grid {
+	fn fits(&self, placement: &Placement) -> bool {
+		placement.cells.iter().all(|cell| self.occupied[*cell] == 0 && self.forbidden[*cell] == 0)
+	}
+
+	fn place(&mut self, placement: &Placement) {
+		placement.cells.iter().for_each(|cell| self.occupied[*cell] += 1);
+		placement.halo.iter().for_each(|cell| self.forbidden[*cell] += 1);
+	}
+
+	fn lift(&mut self, placement: &Placement) {
+		placement.cells.iter().for_each(|cell| self.occupied[*cell] -= 1);
+		placement.halo.iter().for_each(|cell| self.forbidden[*cell] -= 1);
+	}
+}
+
+// the arrangements found so far and how many of them cover each cell
+struct Tally {
+	covered: Vec<u64>,
+	arrangements: u64,
+}
+
+impl Tally {
+	// the ships on the grid only make an arrangement when they cover every hit
+	fn add(&mut self, grid: &Grid, hits: &[usize]) {
+		if hits.iter().all(|cell| grid.occupied[*cell] > 0) {
+			self.arrangements += 1;
+			for (cell, occupied) in grid.occupied.iter().enumerate() {
+				if *occupied > 0 {
+					self.covered[cell] += 1;
+				}
+			}
+		}
+	}
+}
+
+struct Search {
+	// the placements of every ship afloat, ships of the same class follow each other
+	ships: Vec<(ShipClass, Vec<Placement>)>,
+	hits: Vec<usize>,
+	grid: Grid,
+	tally: Tally,
+	nodes: u64,
+	max_nodes: u64,
+	deadline: Option<Instant>,
+	is_out_of_time: bool,
+}
+
+impl Search {
+	// place ship after ship, a ship of the same class as the one before only takes later placements so no
+	// arrangement is counted twice
+	fn enumerate(&mut self, ship: usize, first: usize) {
+		self.nodes += 1;
+		if self.nodes > self.max_nodes || (self.nodes.is_multiple_of(1024) && is_past(self.deadline)) {
+			self.is_out_of_time = true;
+		}
+		if self.is_out_of_time {
+			return;
+		}
+
+		if ship == self.ships.len() {
+			self.tally.add(&self.grid, &self.hits);
+			return;
+		}
+
+		// the hits left uncovered have to fit into the ships left
+		let uncovered = self.hits.iter().filter(|cell| self.grid.occupied[**cell] == 0).count();
+		let room: usize = self.ships[ship..].iter().map(|(kind, _)| kind.size).sum();
+		if uncovered > room {
+			return;
+		}
+
+		let placements = std::mem::take(&mut self.ships[ship].1);
+		for (index, placement) in placements.iter().enumerate().skip(first) {
+			if self.grid.fits(placement) {
+				self.grid.place(placement);
+				let is_same_class = self.ships.get(ship + 1).is_some_and(|(kind, _)| *kind == self.ships[ship].0);
+				self.enumerate(ship + 1, if is_same_class { index + 1 } else { 0 });
+				self.grid.lift(placement);
+			}
+		}
+		self.ships[ship].1 = placements;
+	}
+
+	// draw a placement for every ship at random and keep the arrangement only when it is legal, every legal
+	// arrangement is equally likely to be kept
+	fn sample<R: Rng>(&mut self, rng: &mut R) {
+		let mut placed: Vec<&Placement> = Vec::new();
+
+		for (_, placements) in &self.ships {
+			if placements.is_empty() {
+				break;
+			}
+			let placement = &placements[rng.gen_range(0..placements.len())];
+			if !self.grid.fits(placement) {
+				break;
+			}
+
+			self.grid.place(placement);
+			placed.push(placement);
+		}
+
+		if placed.len() == self.ships.len() {
+			self.tally.add(&self.grid, &self.hits);
+		}
+		for placement in placed {
+			self.grid.lift(placement);
+		}
+	}
+}
+
+fn is_past(deadline: Option<Instant>) -> bool {
+	deadline.is_some_and(|deadline| Instant::now() > deadline)
+}
+
+/// The hit probability of every cell given the misses, hits and sunk ships of an observation
+///
+/// Every arrangement is counted unless that takes more steps than the budget allows, then the counts are thrown away
+/// and random arrangements are sampled instead. Without a time limit the result only depends on the observation, the
+/// budget and the random numbers.
+pub fn solve(observation: &Observation, budget: &Budget, rng: &mut GameRng) -> Posterior {
+	let board = &observation.board;
+	let (size_x, size_y) = (board.size_x(), board.size_y());
+	let index = |(pos_x, pos_y): (usize, usize)| pos_y * size_x + pos_x;
+	let started = Instant::now();
+
+	// misses, sunk ships and the water around them are off limits
+	let mut blocked: Vec<bool> = board.rows().flatten().map(|cell| *cell == Cell::Shot).collect();
+	for ship in &observation.sunk {
+		let surroundings = movement::get_surroundings(board, &ship.cells, observation.adjacency);
+		for cell in ship.cells.iter().chain(&surroundings) {
+			blocked[index(*cell)] = true;
+		}
+	}
+	let hits: Vec<usize> = board
+		.rows()
+		.flatten()
+		.enumerate()
+		.filter(|(cell, state)| **state == Cell::Damage && !blocked[*cell])
+		.map(|(cell, _)| cell)
+		.collect();
+
+	// biggest ships first so conflicts show up early
+	let mut afloat = observation.get_afloat();
+	afloat.sort_by_key(|kind| std::cmp::Reverse(kind.size));
+	let mut ships: Vec<(ShipClass, Vec<Placement>)> = Vec::new();
+	for kind in afloat {
+		for _ in 0..kind.amount {
+			let mut placements = Vec::new();
+			for (pos_x, pos_y, rotation) in ai::get_candidates(board, kind.size) {
+				let cells = movement::get_coordinates(pos_x, pos_y, kind.size, &rotation);
+				let halo = movement::get_surroundings(board, &cells, observation.adjacency);
+				let cells: Vec<usize> = cells.into_iter().map(index).collect();
+				let halo: Vec<usize> = halo.into_iter().map(index).collect();
+
+				// a hit in the halo would belong to a ship touching this one
+				if cells.iter().any(|cell| blocked[*cell]) || halo.iter().any(|cell| hits.contains(cell)) {
+					continue;
+				}
+				placements.push(Placement { cells, halo });
+			}
+			ships.push((kind.clone(), placements));
+		}
+	}
+
+	let mut search = Search {
+		ships,
+		hits,
+		grid: Grid {
+			occupied: vec![0; size_x * size_y],
+			forbidden: vec![0; size_x * size_y],
+		},
+		tally: Tally {
+			covered: vec![0; size_x * size_y],
+			arrangements: 0,
+		},
+		nodes: 0,
+		max_nodes: budget.nodes,
+		deadline: budget.time.map(|time| started + time / 2),
+		is_out_of_time: false,
+	};
+	search.enumerate(0, 0);
+
+	let is_exact = !search.is_out_of_time;
+	if !is_exact {
+		search.tally = Tally {
+			covered: vec![0; size_x * size_y],
+			arrangements: 0,
+		};
+
+		let deadline = budget.time.map(|time| started + time);
+		for sample in 0..budget.samples {
+			if sample.is_multiple_of(64) && is_past(deadline) {
+				break;
+			}
+			search.sample(rng);
+		}
+	}
+
+	Posterior {
+		size_x,
+		size_y,
+		covered: search.tally.covered,
+		arrangements: search.tally.arrangements,
+		is_exact,
+	}
+}
+
+#[cfg(test)]
+use crate::{board::Board, config, engine::Rules, movement::Adjacency, notation};
+#[cfg(test)]
+use rand::SeedableRng;
+
+#[cfg(test)]
+fn solve_text(text: &str, fleet: Vec<ShipClass>, adjacency: Adjacency) -> Posterior {
+	let board = notation::parse(text).unwrap();
+	let rules = Rules {
+		fleet,
+		adjacency,
+		..Rules::default()
+	};
+
+	solve(&Observation::new(&board, &board, &rules), &config::SOLVER_BUDGET, &mut GameRng::seed_from_u64(0))
+}
+
+#[cfg(test)]
+fn get_row(posterior: &Posterior, pos_y: usize) -> Vec<f64> {
+	(0..posterior.size_x).map(|pos_x| (posterior.get(pos_x, pos_y) * 100.0).round() / 100.0).collect()
+}
+
+#[test]
+fn solve_works() {
+	let destroyer = ShipClass::new("Destroyer", 2, 1);
+
+	let posterior = solve_text("....\n", vec![destroyer.clone()], Adjacency::Any);
+	assert!(posterior.is_exact());
+	assert_eq!(posterior.arrangements(), 3);
+	assert_eq!(get_row(&posterior, 0), vec![0.33, 0.67, 0.67, 0.33]);
+
+	// the hit has to be covered
+	let posterior = solve_text("..x.\n", vec![destroyer.clone()], Adjacency::Any);
+	assert_eq!(posterior.arrangements(), 2);
+	assert_eq!(get_row(&posterior, 0), vec![0.0, 0.5, 1.0, 0.5]);
+
+	// two ships of a class are counted once per arrangement
+	let posterior = solve_text(".....\n", vec![ShipClass::new("Destroyer", 2, 2)], Adjacency::Any);
+	assert_eq!(posterior.arrangements(), 3);
+	assert_eq!(get_row(&posterior, 0), vec![0.67, 1.0, 0.67, 1.0, 0.67]);
+
+	// ships that may not touch need a gap
+	let posterior = solve_text(".....\n", vec![ShipClass::new("Destroyer", 2, 2)], Adjacency::NoEdge);
+	assert_eq!(posterior.arrangements(), 1);
+	assert_eq!(get_row(&posterior, 0), vec![1.0, 1.0, 0.0, 1.0, 1.0]);
+}
+
+#[test]
+fn solve_knows_sunk_ships() {
+	// the sunk destroyer leaves water around it and the submarine can only go to the right
+	let text = "aa.....\n.......\n\nA Destroyer\n";
+	let fleet = vec![ShipClass::new("Destroyer", 2, 1), ShipClass::new("Submarine", 3, 1)];
+	let posterior = solve_text(text, fleet, Adjacency::NoEdgeOrCorner);
+	assert_eq!(get_row(&posterior, 0), vec![0.0, 0.0, 0.0, 0.25, 0.5, 0.5, 0.25]);
+	assert_eq!(get_row(&posterior, 1), vec![0.0, 0.0, 0.0, 0.25, 0.5, 0.5, 0.25]);
+	assert_eq!(posterior.arrangements(), 4);
+}
+
+#[test]
+fn solve_samples_big_searches() {
+	let board = Board::default();
+	let rules = Rules {
+		fleet: config::get_classic_fleet(),
+		..Rules::default()
+	};
+
+	let observation = Observation::new(&board, &board, &rules);
+	let budget = Budget {
+		nodes: 10_000,
+		samples: 20_000,
+		time: None,
+	};
+	let posterior = solve(&observation, &budget, &mut GameRng::seed_from_u64(0));
+	assert!(!posterior.is_exact());
+	assert!(posterior.arrangements() > 0);
+	// the corners are the least likely cells and the centre the most likely
+	assert!(posterior.get(0, 0) < posterior.get(4, 4));
+	assert!((0..10).all(|pos_x| posterior.get(pos_x, 0) <= 1.0));
+
+	// the same random numbers give the same samples on any machine
+	assert_eq!(solve(&observation, &budget, &mut GameRng::seed_from_u64(0)), posterior);
+
+	// a clock that ran out stops both the count and the samples
+	let budget = Budget {
+		time: Some(Duration::ZERO),
+		..budget
+	};
+	let posterior = solve(&observation, &budget, &mut GameRng::seed_from_u64(0));
+	assert!(!posterior.is_exact());
+	assert_eq!(posterior.arrangements(), 0);
+}